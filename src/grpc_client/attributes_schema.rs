@@ -0,0 +1,240 @@
+use std::time::Duration;
+
+use proc_macro2::{Delimiter, Ident, TokenStream, TokenTree};
+
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(3);
+pub const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_RETRIES: usize = 3;
+pub const DEFAULT_ENDPOINTS_REFRESH: Duration = Duration::from_secs(30);
+
+// Nested keys are checked for objects and for lists of objects (overrides)
+pub struct ParamSchema {
+    pub name: &'static str,
+    pub nested: &'static [ParamSchema],
+}
+
+const fn param(name: &'static str) -> ParamSchema {
+    ParamSchema { name, nested: &[] }
+}
+
+const fn object(name: &'static str, nested: &'static [ParamSchema]) -> ParamSchema {
+    ParamSchema { name, nested }
+}
+
+const METADATA: &[ParamSchema] = &[param("key"), param("value")];
+
+const RATE_LIMIT: &[ParamSchema] = &[param("per_sec"), param("burst")];
+
+const HEDGE: &[ParamSchema] = &[param("after"), param("after_ms"), param("max_extra")];
+
+const CACHE: &[ParamSchema] = &[param("ttl"), param("ttl_ms"), param("max_entries")];
+
+const FAILOVER: &[ParamSchema] = &[
+    param("secondary_service_name"),
+    param("failback_after"),
+    param("failback_after_sec"),
+];
+
+pub const OVERRIDE_SCHEMA: &[ParamSchema] = &[
+    param("fn_name"),
+    param("retries"),
+    param("idempotent"),
+    object("hedge", HEDGE),
+    object("rate_limit", RATE_LIMIT),
+    param("max_concurrency"),
+    param("on_limit"),
+    object("cache", CACHE),
+    param("single_flight"),
+    param("log_payloads"),
+    param("rename_to"),
+    param("visibility"),
+];
+
+pub const CLIENT_SCHEMA: &[ParamSchema] = &[
+    param("config_file"),
+    param("proto_file"),
+    param("crate_ns"),
+    param("retries"),
+    param("request_timeout"),
+    param("request_timeout_sec"),
+    param("ping_timeout"),
+    param("ping_timeout_sec"),
+    param("ping_interval"),
+    param("ping_interval_sec"),
+    param("telemetry"),
+    param("service_name"),
+    param("fake_server"),
+    param("mock"),
+    param("blocking"),
+    param("client_options"),
+    param("record_replay"),
+    param("load_balancing"),
+    param("endpoints_refresh"),
+    object("failover", FAILOVER),
+    param("include"),
+    param("exclude"),
+    object("rate_limit", RATE_LIMIT),
+    param("max_concurrency"),
+    param("on_limit"),
+    param("log_payloads"),
+    param("redact"),
+    param("log_payloads_max_size"),
+    object("metadata", METADATA),
+    object("overrides", OVERRIDE_SCHEMA),
+];
+
+// Splits "key: value, key: value" into pairs. Objects and lists are single groups, so only the top level is split
+pub fn read_params(tokens: &TokenStream) -> Vec<(Ident, TokenStream)> {
+    let mut result = Vec::new();
+    let mut tokens = tokens.clone().into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        let key = match token {
+            TokenTree::Ident(key) => key,
+            _ => continue,
+        };
+
+        match tokens.peek() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ':' => {
+                tokens.next();
+            }
+            _ => continue,
+        }
+
+        let mut value = Vec::new();
+
+        for token in tokens.by_ref() {
+            match token {
+                TokenTree::Punct(punct) if punct.as_char() == ',' => break,
+                token => value.push(token),
+            }
+        }
+
+        result.push((key, value.into_iter().collect()));
+    }
+
+    result
+}
+
+pub fn check_keys(tokens: &TokenStream, schema: &[ParamSchema]) -> Result<(), syn::Error> {
+    for (key, value) in read_params(tokens) {
+        let name = key.to_string();
+
+        let param = match schema.iter().find(|param| param.name == name) {
+            Some(param) => param,
+            None => {
+                let message = match get_suggestion(name.as_str(), schema) {
+                    Some(suggestion) => {
+                        format!("Unknown parameter {}. Did you mean {}?", name, suggestion)
+                    }
+                    None => format!("Unknown parameter {}", name),
+                };
+
+                return Err(syn::Error::new_spanned(key, message));
+            }
+        };
+
+        if param.nested.is_empty() {
+            continue;
+        }
+
+        for token in value {
+            if let TokenTree::Group(group) = token {
+                match group.delimiter() {
+                    Delimiter::Brace => check_keys(&group.stream(), param.nested)?,
+                    Delimiter::Bracket => check_object_list(&group.stream(), param.nested)?,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_object_list(tokens: &TokenStream, schema: &[ParamSchema]) -> Result<(), syn::Error> {
+    for token in tokens.clone() {
+        if let TokenTree::Group(group) = token {
+            if group.delimiter() == Delimiter::Brace {
+                check_keys(&group.stream(), schema)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn get_suggestion(name: &str, schema: &[ParamSchema]) -> Option<&'static str> {
+    let max_distance = (name.len() / 3).max(1);
+
+    schema
+        .iter()
+        .map(|param| (get_distance(name, param.name), param.name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+// Levenshtein distance
+fn get_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use proc_macro2::TokenStream;
+
+    use super::*;
+
+    fn check(src: &str) -> Result<(), String> {
+        let tokens = TokenStream::from_str(src).unwrap();
+        check_keys(&tokens, CLIENT_SCHEMA).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_known_keys() {
+        let result = check(
+            r#"proto_file: "./a.proto", crate_ns: "crate::a", retries: 3,
+            failover: { secondary_service_name: "B", failback_after: "1m" },
+            overrides: [ { fn_name: "Get", hedge: { after: "50ms", max_extra: 1 } }, { fn_name: "Set", retries: 0 } ]"#,
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_suggestion() {
+        assert_eq!(
+            check(r#"proto_file: "./a.proto", retires: 3"#).unwrap_err(),
+            "Unknown parameter retires. Did you mean retries?"
+        );
+
+        assert_eq!(
+            check(r#"overrides: [ { fn_name: "Get", cache: { ttl: "1s", max_entires: 10 } } ]"#)
+                .unwrap_err(),
+            "Unknown parameter max_entires. Did you mean max_entries?"
+        );
+
+        assert_eq!(
+            check(r#"something: true"#).unwrap_err(),
+            "Unknown parameter something"
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::client_fields::ClientFields;
+
+pub struct RateLimit {
+    pub per_sec: usize,
+    pub burst: usize,
+}
+
+#[derive(Clone, Copy)]
+pub enum OnLimit {
+    Wait,
+    FailFast,
+}
+
+pub struct CallLimits {
+    pub rate_limit: Option<RateLimit>,
+    pub max_concurrency: Option<usize>,
+    pub on_limit: Option<OnLimit>,
+}
+
+impl CallLimits {
+    pub fn new(params: &ParamsList) -> Result<Self, syn::Error> {
+        let rate_limit = match params.try_get_named_param("rate_limit") {
+            Some(value) => {
+                let value = value.unwrap_as_single_object()?;
+                let rate_limit = RateLimit {
+                    per_sec: value
+                        .get_named_param("per_sec")?
+                        .unwrap_as_number_value()?
+                        .as_usize(),
+                    burst: value
+                        .get_named_param("burst")?
+                        .unwrap_as_number_value()?
+                        .as_usize(),
+                };
+
+                if rate_limit.per_sec == 0 || rate_limit.burst == 0 {
+                    return Err(syn::Error::new_spanned(
+                        value.get_token_stream().clone(),
+                        "per_sec and burst must be greater than 0",
+                    ));
+                }
+
+                Some(rate_limit)
+            }
+            None => None,
+        };
+
+        let max_concurrency = match params.try_get_named_param("max_concurrency") {
+            Some(value) => {
+                let max_concurrency = value.unwrap_as_number_value()?.as_usize();
+                if max_concurrency == 0 {
+                    return Err(value.throw_error("max_concurrency must be greater than 0"));
+                }
+                Some(max_concurrency)
+            }
+            None => None,
+        };
+
+        let on_limit = match params.try_get_named_param("on_limit") {
+            Some(value) => match value.unwrap_as_string_value()?.as_str() {
+                "wait" => Some(OnLimit::Wait),
+                "fail_fast" => Some(OnLimit::FailFast),
+                _ => return Err(value.throw_error("on_limit must be \"wait\" or \"fail_fast\"")),
+            },
+            None => None,
+        };
+
+        Ok(Self {
+            rate_limit,
+            max_concurrency,
+            on_limit,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rate_limit.is_none() && self.max_concurrency.is_none()
+    }
+
+    pub fn add_fields(
+        &self,
+        field_prefix: &str,
+        limiter_name: &TokenStream,
+        client_fields: &mut ClientFields,
+    ) {
+        if let Some(rate_limit) = &self.rate_limit {
+            let per_sec = proc_macro2::Literal::f64_unsuffixed(rate_limit.per_sec as f64);
+            let burst = proc_macro2::Literal::f64_unsuffixed(rate_limit.burst as f64);
+
+            client_fields.add(
+                &get_rate_limiter_field(field_prefix),
+                quote::quote!(#limiter_name),
+                quote::quote!(#limiter_name::new(#per_sec, #burst)),
+            );
+        }
+
+        if let Some(max_concurrency) = self.max_concurrency {
+            let max_concurrency = proc_macro2::Literal::usize_unsuffixed(max_concurrency);
+
+            client_fields.add(
+                &get_concurrency_field(field_prefix),
+                quote::quote!(tokio::sync::Semaphore),
+                quote::quote!(tokio::sync::Semaphore::new(#max_concurrency)),
+            );
+        }
+    }
+
+    // on_limit is passed by the caller, since on_limit of the method applies to the client-wide limits as well
+    pub fn generate_acquire(
+        &self,
+        struct_name: &Ident,
+        field_prefix: &str,
+        rpc_name: &str,
+        on_limit: OnLimit,
+    ) -> TokenStream {
+        let mut result = Vec::new();
+
+        if self.rate_limit.is_some() {
+            let field = get_rate_limiter_field(field_prefix);
+            let acquire = match on_limit {
+                OnLimit::Wait => quote::quote!(self.#field.acquire().await;),
+                OnLimit::FailFast => {
+                    let message = format!("Client side rate limit is exceeded for {}", rpc_name);
+                    let return_error = super::client_errors::generate_return_client_error(
+                        struct_name,
+                        quote::quote!(RateLimitExceeded),
+                        quote::quote!(#message.to_string()),
+                    );
+
+                    quote::quote! {
+                        if self.#field.try_acquire().is_err() {
+                            #return_error
+                        }
+                    }
+                }
+            };
+            result.push(acquire);
+        }
+
+        if self.max_concurrency.is_some() {
+            let field = get_concurrency_field(field_prefix);
+            let permit = TokenStream::from_str(format!("_{}permit", field_prefix).as_str()).unwrap();
+
+            let acquire = match on_limit {
+                OnLimit::Wait => quote::quote!(let #permit = self.#field.acquire().await.unwrap();),
+                OnLimit::FailFast => {
+                    let message = format!("Client side concurrency limit is exceeded for {}", rpc_name);
+                    let return_error = super::client_errors::generate_return_client_error(
+                        struct_name,
+                        quote::quote!(ConcurrencyLimitExceeded),
+                        quote::quote!(#message.to_string()),
+                    );
+
+                    quote::quote! {
+                        let #permit = match self.#field.try_acquire() {
+                            Ok(permit) => permit,
+                            Err(_) => {
+                                #return_error
+                            }
+                        };
+                    }
+                }
+            };
+            result.push(acquire);
+        }
+
+        quote::quote!(#(#result)*)
+    }
+}
+
+pub fn get_rate_limiter_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}RateLimiter", struct_name).as_str()).unwrap()
+}
+
+fn get_rate_limiter_field(field_prefix: &str) -> TokenStream {
+    TokenStream::from_str(format!("{}rate_limiter", field_prefix).as_str()).unwrap()
+}
+
+fn get_concurrency_field(field_prefix: &str) -> TokenStream {
+    TokenStream::from_str(format!("{}concurrency_limiter", field_prefix).as_str()).unwrap()
+}
+
+// Token bucket shared by all the calls which are limited by the same rate_limit setting.
+// It goes by the tokio clock, the same as the sleep which waits for a token, so paused time of tests applies to both
+pub fn generate_rate_limiter_struct(limiter_name: &TokenStream) -> TokenStream {
+    quote::quote! {
+        struct #limiter_name {
+            per_sec: f64,
+            burst: f64,
+            state: std::sync::Mutex<(f64, tokio::time::Instant)>,
+        }
+
+        impl #limiter_name {
+            fn new(per_sec: f64, burst: f64) -> Self {
+                Self {
+                    per_sec,
+                    burst,
+                    state: std::sync::Mutex::new((burst, tokio::time::Instant::now())),
+                }
+            }
+
+            fn try_acquire(&self) -> Result<(), std::time::Duration> {
+                let mut state = self.state.lock().unwrap();
+                let now = tokio::time::Instant::now();
+                let elapsed = now.duration_since(state.1).as_secs_f64();
+                state.0 = (state.0 + elapsed * self.per_sec).min(self.burst);
+                state.1 = now;
+
+                if state.0 >= 1.0 {
+                    state.0 -= 1.0;
+                    return Ok(());
+                }
+
+                Err(std::time::Duration::from_secs_f64(
+                    (1.0 - state.0) / self.per_sec,
+                ))
+            }
+
+            async fn acquire(&self) {
+                while let Err(delay) = self.try_acquire() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
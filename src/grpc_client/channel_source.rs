@@ -0,0 +1,80 @@
+use proc_macro2::{Ident, TokenStream};
+
+pub enum ChannelSource {
+    Single,
+    LoadBalancing,
+    Failover,
+}
+
+impl ChannelSource {
+    // Channels are dropped once the client is shut down, so a call which passed the shutdown check
+    // may find none of them
+    pub fn generate_get_channel(&self, struct_name: &Ident, get_channel: TokenStream) -> TokenStream {
+        let return_channel_closed = super::shutdown::generate_return_channel_closed(struct_name);
+        let error_name = super::client_errors::get_client_error_name(struct_name);
+        let return_no_endpoints = super::client_errors::generate_return_client_error(
+            struct_name,
+            quote::quote!(NoEndpoints),
+            quote::quote!(format!(
+                "No grpc urls are configured for service {}",
+                #struct_name::get_service_name()
+            )),
+        );
+
+        match self {
+            Self::Single => quote::quote! {
+                let grpc_channel = match self.channel.get() {
+                    Some(grpc_channel) => grpc_channel,
+                    None => {
+                        #return_channel_closed
+                    }
+                };
+                let channel = grpc_channel.#get_channel;
+            },
+            Self::LoadBalancing => quote::quote! {
+                let endpoint = match self.endpoints.get_next().await {
+                    Ok(endpoint) => endpoint,
+                    Err(#error_name::NoEndpoints) => {
+                        #return_no_endpoints
+                    }
+                    Err(_) => {
+                        #return_channel_closed
+                    }
+                };
+                let grpc_channel = match endpoint.channel.get() {
+                    Some(grpc_channel) => grpc_channel,
+                    None => {
+                        #return_channel_closed
+                    }
+                };
+                let _outstanding = endpoint.start_request();
+                let channel = grpc_channel.#get_channel;
+            },
+            Self::Failover => quote::quote! {
+                let grpc_channel = match self.failover.get_active() {
+                    Some(grpc_channel) => grpc_channel,
+                    None => {
+                        #return_channel_closed
+                    }
+                };
+                let channel = grpc_channel.#get_channel;
+            },
+        }
+    }
+
+    pub fn generate_reconnect_channels(&self) -> TokenStream {
+        match self {
+            Self::Single => quote::quote!(self.channel.reconnect();),
+            Self::LoadBalancing => quote::quote!(self.endpoints.reconnect();),
+            Self::Failover => quote::quote!(self.failover.reconnect();),
+        }
+    }
+
+    pub fn generate_close_channels(&self) -> TokenStream {
+        match self {
+            Self::Single => quote::quote!(self.channel.close();),
+            Self::LoadBalancing => quote::quote!(self.endpoints.close();),
+            Self::Failover => quote::quote!(self.failover.close();),
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::{str::FromStr, time::Duration};
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::client_fields::ClientFields;
+
+pub fn get_timings_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Timings", struct_name).as_str()).unwrap()
+}
+
+pub fn get_builder_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Builder", struct_name).as_str()).unwrap()
+}
+
+fn get_user_fields_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}UserFields", struct_name).as_str()).unwrap()
+}
+
+pub fn add_builder_fields(
+    struct_name: &Ident,
+    client_fields: &mut ClientFields,
+    with_client_options: bool,
+) {
+    if with_client_options {
+        let live_options_name = super::client_options::get_live_options_name(struct_name);
+
+        client_fields.add(
+            &quote::quote!(live_options),
+            quote::quote!(std::sync::Arc<#live_options_name>),
+            quote::quote!(live_options),
+        );
+    } else {
+        client_fields.add(
+            &quote::quote!(retries),
+            quote::quote!(usize),
+            quote::quote!(self.retries),
+        );
+    }
+
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+
+    client_fields.add(
+        &quote::quote!(shutdown),
+        quote::quote!(std::sync::Arc<#shutdown_name>),
+        quote::quote!(shutdown),
+    );
+
+    client_fields.add(
+        &quote::quote!(metadata),
+        quote::quote!(Vec<(tonic::metadata::AsciiMetadataKey, tonic::metadata::AsciiMetadataValue)>),
+        quote::quote!(self.metadata),
+    );
+}
+
+pub struct BuilderDefaults {
+    pub request_timeout: Duration,
+    pub ping_timeout: Duration,
+    pub ping_interval: Duration,
+    pub retries: usize,
+    pub metadata: Vec<(String, String)>,
+}
+
+// Keys and values are checked here, since AsciiMetadataKey::from_static panics at runtime on invalid ones
+pub fn read_metadata(attributes: &ParamsList) -> Result<Vec<(String, String)>, syn::Error> {
+    let mut result = Vec::new();
+
+    let value = match attributes.try_get_named_param("metadata") {
+        Some(value) => value,
+        None => return Ok(result),
+    };
+
+    for item in value.unwrap_as_object_list()?.iter() {
+        let key_param = item.get_named_param("key")?;
+        let key = key_param.unwrap_as_string_value()?.to_string();
+
+        let is_valid_key = !key.is_empty()
+            && key.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_' || c == '.'
+            });
+
+        if !is_valid_key {
+            return Err(key_param.throw_error(
+                "Metadata key must be a lowercase ascii header name like \"x-source\"",
+            ));
+        }
+
+        // Keys ending with -bin carry binary values, which from_static of AsciiMetadataKey panics on
+        if key.ends_with("-bin") {
+            return Err(key_param.throw_error(
+                "Binary metadata keys (ending with -bin) are not supported. Use an ascii key",
+            ));
+        }
+
+        let value_param = item.get_named_param("value")?;
+        let value = value_param.unwrap_as_string_value()?.to_string();
+
+        if !value.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+            return Err(value_param.throw_error("Metadata value must be a printable ascii string"));
+        }
+
+        result.push((key, value));
+    }
+
+    Ok(result)
+}
+
+// Values of the macro attributes are the defaults of the builder, so timings can be tuned at runtime.
+// Returns the builder types and the builder()/new() methods of the client
+pub fn generate_builder(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    settings_type: &TokenStream,
+    channel_initializer: &TokenStream,
+    client_fields: &ClientFields,
+    defaults: &BuilderDefaults,
+    with_client_options: bool,
+) -> (TokenStream, TokenStream) {
+    let timings_name = get_timings_name(struct_name);
+    let builder_name = get_builder_name(struct_name);
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+    let user_fields_name = get_user_fields_name(struct_name);
+
+    let request_timeout = super::duration_param::duration_to_tokens(defaults.request_timeout);
+    let ping_timeout = super::duration_param::duration_to_tokens(defaults.ping_timeout);
+    let ping_interval = super::duration_param::duration_to_tokens(defaults.ping_interval);
+    let retries = proc_macro2::Literal::usize_unsuffixed(defaults.retries);
+    let metadata_keys = defaults.metadata.iter().map(|(key, _)| key.as_str());
+    let metadata_values = defaults.metadata.iter().map(|(_, value)| value.as_str());
+
+    let initializers = &client_fields.initializers;
+    let constructor_params = &client_fields.constructor_params;
+    let constructor_args = &client_fields.constructor_args;
+
+    let (create_live_options, fn_build_with_options) = if with_client_options {
+        let live_options_name = super::client_options::get_live_options_name(struct_name);
+        let read_options = super::client_options::generate_read_options_on_build(struct_name);
+
+        (
+            quote::quote! {
+                let live_options = std::sync::Arc::new(#live_options_name::new(
+                    self.retries,
+                    self.timings.request_timeout,
+                    self.timings.ping_timeout,
+                    self.timings.ping_interval,
+                ));
+            },
+            quote::quote! {
+                pub async fn build_with_options(
+                    mut self,
+                    #(#constructor_params,)*
+                ) -> Result<#struct_name, String> {
+                    #read_options
+                    self.timings.validate()?;
+                    Ok(self.build_with_user_fields(#user_fields_name { #(#constructor_args,)* }))
+                }
+            },
+        )
+    } else {
+        (quote::quote!(), quote::quote!())
+    };
+
+    let builder_types = quote::quote! {
+        // Fields of the annotated struct which are passed to new() and build(). #[cfg] of the fields is kept
+        struct #user_fields_name {
+            #(#constructor_params,)*
+        }
+
+        #[derive(Clone, Copy, Debug)]
+        struct #timings_name {
+            request_timeout: std::time::Duration,
+            ping_timeout: std::time::Duration,
+            ping_interval: std::time::Duration,
+        }
+
+        impl #timings_name {
+            // Defaults are checked by the macro, so only the values set at runtime can fail
+            fn validate(&self) -> Result<(), String> {
+                if self.request_timeout.is_zero()
+                    || self.ping_timeout.is_zero()
+                    || self.ping_interval.is_zero()
+                {
+                    return Err(format!(
+                        "request_timeout, ping_timeout and ping_interval must not be zero. Got: {:?}",
+                        self
+                    ));
+                }
+
+                // Timings are kept as microseconds in u64 by the live options
+                if self.request_timeout.as_micros() > u64::MAX as u128
+                    || self.ping_timeout.as_micros() > u64::MAX as u128
+                    || self.ping_interval.as_micros() > u64::MAX as u128
+                {
+                    return Err(format!(
+                        "request_timeout, ping_timeout and ping_interval must be less than u64::MAX microseconds. Got: {:?}",
+                        self
+                    ));
+                }
+
+                if self.ping_timeout >= self.ping_interval {
+                    return Err(format!(
+                        "ping_timeout {:?} must be less than ping_interval {:?}",
+                        self.ping_timeout, self.ping_interval
+                    ));
+                }
+
+                Ok(())
+            }
+        }
+
+        #vis struct #builder_name {
+            settings: #settings_type,
+            timings: #timings_name,
+            retries: usize,
+            metadata: Vec<(tonic::metadata::AsciiMetadataKey, tonic::metadata::AsciiMetadataValue)>,
+        }
+
+        impl #builder_name {
+            pub fn request_timeout(mut self, value: std::time::Duration) -> Self {
+                self.timings.request_timeout = value;
+                self
+            }
+
+            pub fn ping_timeout(mut self, value: std::time::Duration) -> Self {
+                self.timings.ping_timeout = value;
+                self
+            }
+
+            pub fn ping_interval(mut self, value: std::time::Duration) -> Self {
+                self.timings.ping_interval = value;
+                self
+            }
+
+            pub fn retries(mut self, value: usize) -> Self {
+                self.retries = value;
+                self
+            }
+
+            // Metadata is added to every request of the client
+            pub fn metadata(
+                mut self,
+                key: tonic::metadata::AsciiMetadataKey,
+                value: tonic::metadata::AsciiMetadataValue,
+            ) -> Self {
+                self.metadata.push((key, value));
+                self
+            }
+
+            pub fn build(self, #(#constructor_params,)*) -> Result<#struct_name, String> {
+                self.timings.validate()?;
+                Ok(self.build_with_user_fields(#user_fields_name { #(#constructor_args,)* }))
+            }
+
+            fn build_with_user_fields(self, user_fields: #user_fields_name) -> #struct_name {
+                let #user_fields_name { #(#constructor_args,)* } = user_fields;
+
+                // Shared with the ping loop, so it stops once the client is shut down
+                let shutdown = std::sync::Arc::new(#shutdown_name::new());
+
+                #create_live_options
+
+                #struct_name {
+                    #channel_initializer,
+                    #(#initializers,)*
+                }
+            }
+
+            #fn_build_with_options
+        }
+    };
+
+    let client_methods = quote::quote! {
+        #vis fn builder(settings: #settings_type) -> #builder_name {
+            #builder_name {
+                settings,
+                timings: #timings_name {
+                    request_timeout: #request_timeout,
+                    ping_timeout: #ping_timeout,
+                    ping_interval: #ping_interval,
+                },
+                retries: #retries,
+                metadata: vec![#((
+                    tonic::metadata::AsciiMetadataKey::from_static(#metadata_keys),
+                    tonic::metadata::AsciiMetadataValue::from_static(#metadata_values),
+                ),)*],
+            }
+        }
+
+        #vis fn new(settings: #settings_type, #(#constructor_params,)*) -> Self {
+            Self::builder(settings).build_with_user_fields(#user_fields_name { #(#constructor_args,)* })
+        }
+    };
+
+    (builder_types, client_methods)
+}
@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_client_error_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Error", struct_name).as_str()).unwrap()
+}
+
+// Errors made by the client itself carry a metadata marker, so they can be told apart
+// from the statuses with the same code which come from the server or the transport
+pub fn generate_client_error_enum(struct_name: &Ident, vis: &syn::Visibility) -> TokenStream {
+    let error_name = get_client_error_name(struct_name);
+
+    quote::quote! {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #vis enum #error_name {
+            RateLimitExceeded,
+            ConcurrencyLimitExceeded,
+            ShutDown,
+            NoEndpoints,
+        }
+
+        impl #error_name {
+            const METADATA_KEY: &'static str = "x-grpc-client-error";
+
+            fn as_str(&self) -> &'static str {
+                match self {
+                    Self::RateLimitExceeded => "rate-limit-exceeded",
+                    Self::ConcurrencyLimitExceeded => "concurrency-limit-exceeded",
+                    Self::ShutDown => "shut-down",
+                    Self::NoEndpoints => "no-endpoints",
+                }
+            }
+
+            fn into_status(self, message: String) -> tonic::Status {
+                let code = match self {
+                    Self::RateLimitExceeded | Self::ConcurrencyLimitExceeded => {
+                        tonic::Code::ResourceExhausted
+                    }
+                    Self::ShutDown | Self::NoEndpoints => tonic::Code::Unavailable,
+                };
+
+                let mut status = tonic::Status::new(code, message);
+                status.metadata_mut().insert(
+                    Self::METADATA_KEY,
+                    tonic::metadata::AsciiMetadataValue::from_static(self.as_str()),
+                );
+                status
+            }
+
+            // Status is rebuilt, since GrpcReadError can not be cloned to be shared or kept
+            fn status_of(err: &my_grpc_extensions::GrpcReadError) -> tonic::Status {
+                match err {
+                    my_grpc_extensions::GrpcReadError::TonicStatus(status) => {
+                        tonic::Status::with_details_and_metadata(
+                            status.code(),
+                            status.message(),
+                            status.details().to_vec().into(),
+                            status.metadata().clone(),
+                        )
+                    }
+                    err => tonic::Status::unknown(format!("{:?}", err)),
+                }
+            }
+
+            pub fn from_status(status: &tonic::Status) -> Option<Self> {
+                let value = status.metadata().get(Self::METADATA_KEY)?.to_str().ok()?;
+
+                [
+                    Self::RateLimitExceeded,
+                    Self::ConcurrencyLimitExceeded,
+                    Self::ShutDown,
+                    Self::NoEndpoints,
+                ]
+                .into_iter()
+                .find(|error| error.as_str() == value)
+            }
+        }
+    }
+}
+
+pub fn generate_return_client_error(
+    struct_name: &Ident,
+    error: TokenStream,
+    message: TokenStream,
+) -> TokenStream {
+    let error_name = get_client_error_name(struct_name);
+
+    quote::quote! {
+        return Err(#error_name::#error.into_status(#message).into());
+    }
+}
@@ -0,0 +1,273 @@
+use proc_macro2::TokenStream;
+
+#[derive(Default)]
+pub struct ClientFields {
+    pub fields: Vec<TokenStream>,
+    pub initializers: Vec<TokenStream>,
+    pub constructor_params: Vec<TokenStream>,
+    pub constructor_args: Vec<TokenStream>,
+    user_field_names: Vec<syn::Ident>,
+    generated_field_names: Vec<String>,
+}
+
+// Reserved whatever the attributes and features are, so enabling one does not break the user fields
+const RESERVED_FIELD_NAMES: &[&str] = &[
+    "endpoints",
+    "failover",
+    "retries",
+    "live_options",
+    "metadata",
+    "shutdown",
+    "fault_injector",
+    "record_replay",
+];
+
+// Derives which need every field to implement the trait. Generated fields hold channels, locks and closures, so they never do
+const UNSUPPORTED_DERIVES: &[&str] = &[
+    "Debug",
+    "Clone",
+    "Copy",
+    "Default",
+    "PartialEq",
+    "Eq",
+    "PartialOrd",
+    "Ord",
+    "Hash",
+];
+
+impl ClientFields {
+    pub fn add(&mut self, name: &TokenStream, field_type: TokenStream, initializer: TokenStream) {
+        self.fields.push(quote::quote!(#name: #field_type));
+        self.initializers.push(quote::quote!(#name: #initializer));
+        self.generated_field_names.push(name.to_string());
+    }
+
+    // Called once all the generated fields are added, so user fields are checked against the ones of this client
+    pub fn check_user_field_names(&self) -> Result<(), syn::Error> {
+        for name in &self.user_field_names {
+            let name_str = name.to_string();
+
+            let is_generated = self.generated_field_names.contains(&name_str)
+                || RESERVED_FIELD_NAMES.contains(&name_str.as_str());
+
+            if is_generated {
+                return Err(syn::Error::new_spanned(
+                    name.clone(),
+                    format!("Field {} is generated by the macro. Rename the field", name_str),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Attributes of the struct are kept, so a derive which can not be implemented for the generated fields
+    // is reported on the attribute instead of the generated code
+    pub fn check_struct_derives(ast: &syn::DeriveInput) -> Result<(), syn::Error> {
+        for attr in &ast.attrs {
+            if !attr.path().is_ident("derive") {
+                continue;
+            }
+
+            let derives = attr.parse_args_with(
+                syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+            )?;
+
+            for derive in derives {
+                let name = match derive.segments.last() {
+                    Some(segment) => segment.ident.to_string(),
+                    None => continue,
+                };
+
+                if UNSUPPORTED_DERIVES.contains(&name.as_str()) {
+                    return Err(syn::Error::new_spanned(
+                        derive,
+                        format!(
+                            "#[derive({})] is not supported, since fields generated by the macro do not implement it",
+                            name
+                        ),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Fields declared by the user are kept as they are and become parameters of the constructor,
+    // unless they are marked with #[grpc_client(default)]. Channel field is generated, so the declared one is only checked
+    pub fn add_user_fields(&mut self, ast: &syn::DeriveInput) -> Result<(), syn::Error> {
+        if !ast.generics.params.is_empty() {
+            return Err(syn::Error::new_spanned(
+                ast.generics.clone(),
+                "Generic grpc client structs are not supported",
+            ));
+        }
+
+        let fields = match &ast.data {
+            syn::Data::Struct(data) => match &data.fields {
+                syn::Fields::Named(fields) => &fields.named,
+                syn::Fields::Unit => return Ok(()),
+                syn::Fields::Unnamed(fields) => {
+                    return Err(syn::Error::new_spanned(
+                        fields.clone(),
+                        "Grpc client struct must have named fields",
+                    ))
+                }
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ast.ident.clone(),
+                    "generate_grpc_client can only be applied to a struct",
+                ))
+            }
+        };
+
+        for field in fields {
+            let name = field.ident.as_ref().unwrap();
+
+            if name == "channel" {
+                check_channel_type(field)?;
+                continue;
+            }
+
+            let mut attrs = Vec::new();
+            let mut with_default = false;
+
+            for attr in &field.attrs {
+                if attr.path().is_ident("grpc_client") {
+                    let value: syn::Ident = attr.parse_args()?;
+
+                    if value != "default" {
+                        return Err(syn::Error::new_spanned(
+                            value,
+                            "Only #[grpc_client(default)] is supported for fields",
+                        ));
+                    }
+
+                    with_default = true;
+                } else {
+                    attrs.push(attr);
+                }
+            }
+
+            let cfg_attrs: Vec<_> = attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("cfg"))
+                .collect();
+
+            self.user_field_names.push(name.clone());
+
+            let vis = &field.vis;
+            let field_type = &field.ty;
+
+            self.fields
+                .push(quote::quote!(#(#attrs)* #vis #name: #field_type));
+
+            if with_default {
+                self.initializers
+                    .push(quote::quote!(#(#cfg_attrs)* #name: Default::default()));
+            } else {
+                // Call arguments can not have #[cfg], so the arguments are passed on as fields of a struct literal
+                self.constructor_params
+                    .push(quote::quote!(#(#cfg_attrs)* #name: #field_type));
+                self.constructor_args.push(quote::quote!(#(#cfg_attrs)* #name));
+                self.initializers.push(quote::quote!(#(#cfg_attrs)* #name));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_channel_type(field: &syn::Field) -> Result<(), syn::Error> {
+    if let syn::Type::Path(type_path) = &field.ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == "GrpcChannel" {
+                return Ok(());
+            }
+        }
+    }
+
+    Err(syn::Error::new_spanned(
+        field.ty.clone(),
+        "channel field is generated by the macro. Its type must be my_grpc_extensions::GrpcChannel<TGrpcService>",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientFields;
+
+    fn parse(input: &str) -> syn::DeriveInput {
+        syn::parse_str(input).unwrap()
+    }
+
+    #[test]
+    fn test_derives() {
+        let ast = parse("#[derive(Debug)] struct Client;");
+        let err = ClientFields::check_struct_derives(&ast).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("#[derive(Debug)] is not supported"));
+
+        let ast = parse("#[derive(std::clone::Clone)] struct Client;");
+        assert!(ClientFields::check_struct_derives(&ast).is_err());
+
+        let ast = parse("#[derive(MyDerive)] #[allow(dead_code)] struct Client;");
+        assert!(ClientFields::check_struct_derives(&ast).is_ok());
+    }
+
+    #[test]
+    fn test_user_fields() {
+        let ast = parse(
+            "struct Client {
+                app_name: String,
+                #[grpc_client(default)]
+                stats: Vec<usize>,
+                #[cfg(test)]
+                test_value: usize,
+            }",
+        );
+
+        let mut fields = ClientFields::default();
+        fields.add_user_fields(&ast).unwrap();
+
+        let params: Vec<_> = fields
+            .constructor_params
+            .iter()
+            .map(|param| param.to_string())
+            .collect();
+
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0], "app_name : String");
+        assert_eq!(params[1], "# [cfg (test)] test_value : usize");
+
+        let initializers: Vec<_> = fields
+            .initializers
+            .iter()
+            .map(|initializer| initializer.to_string())
+            .collect();
+
+        assert!(initializers.contains(&"stats : Default :: default ()".to_string()));
+        assert!(initializers.contains(&"# [cfg (test)] test_value".to_string()));
+    }
+
+    #[test]
+    fn test_generated_field_name() {
+        let ast = parse("struct Client { shutdown: bool }");
+
+        let mut fields = ClientFields::default();
+        fields.add_user_fields(&ast).unwrap();
+
+        assert!(fields.check_user_field_names().is_err());
+    }
+
+    #[test]
+    fn test_unknown_field_attribute() {
+        let ast = parse("struct Client { #[grpc_client(skip)] value: usize }");
+
+        let mut fields = ClientFields::default();
+        assert!(fields.add_user_fields(&ast).is_err());
+    }
+}
@@ -0,0 +1,309 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_client_options_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Options", struct_name).as_str()).unwrap()
+}
+
+pub fn get_options_settings_trait_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Settings", struct_name).as_str()).unwrap()
+}
+
+pub fn get_live_options_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}LiveOptions", struct_name).as_str()).unwrap()
+}
+
+pub fn get_options_reader_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}OptionsReader", struct_name).as_str()).unwrap()
+}
+
+pub fn generate_read_options_on_build(struct_name: &Ident) -> TokenStream {
+    let options_settings_trait_name = get_options_settings_trait_name(struct_name);
+
+    quote::quote! {
+        let options = #options_settings_trait_name::get_grpc_client_options(
+            self.settings.as_ref(),
+            #struct_name::get_service_name(),
+        )
+        .await;
+
+        if let Some(value) = options.request_timeout {
+            self.timings.request_timeout = value;
+        }
+
+        if let Some(value) = options.ping_timeout {
+            self.timings.ping_timeout = value;
+        }
+
+        if let Some(value) = options.ping_interval {
+            self.timings.ping_interval = value;
+        }
+
+        if let Some(value) = options.retries {
+            self.retries = value;
+        }
+    }
+}
+
+// Timeout from the options is applied to each attempt inside the executor, so every retry gets the whole of it.
+// GrpcChannel keeps the request timeout the client is built with, so a longer one from the options is capped by it
+pub fn wrap_with_live_request_timeout(rpc_name: &str, service_call: TokenStream) -> TokenStream {
+    quote::quote! {
+        {
+            let request_timeout = self.live_options.get_request_timeout();
+
+            match tokio::time::timeout(request_timeout, #service_call).await {
+                Ok(result) => result,
+                Err(_) => Err(tonic::Status::deadline_exceeded(format!(
+                    "{} attempt is not finished in {:?}",
+                    #rpc_name, request_timeout
+                ))),
+            }
+        }
+    }
+}
+
+// Ping loop of GrpcChannel ticks with the ping_interval the client is built with. Ticks are skipped until
+// ping_interval of the options passes and the ping is cut by ping_timeout of the options,
+// so both are applied on the next tick after the options are read
+pub fn generate_ping_call(with_client_options: bool, on_skipped_ping: TokenStream) -> TokenStream {
+    if !with_client_options {
+        return quote::quote!(service.ping(()).await);
+    }
+
+    quote::quote! {
+        {
+            if !self.live_options.is_ping_due() {
+                #on_skipped_ping
+                return;
+            }
+
+            let ping_timeout = self.live_options.get_ping_timeout();
+
+            match tokio::time::timeout(ping_timeout, service.ping(())).await {
+                Ok(result) => result,
+                Err(_) => Err(tonic::Status::deadline_exceeded(format!(
+                    "Ping is not answered in {:?}",
+                    ping_timeout
+                ))),
+            }
+        }
+    }
+}
+
+// Field of the service factory, so the ping reads the options
+pub fn generate_factory_live_options_field(struct_name: &Ident, with_client_options: bool) -> TokenStream {
+    if !with_client_options {
+        return quote::quote!();
+    }
+
+    let live_options_name = get_live_options_name(struct_name);
+
+    quote::quote!(live_options: std::sync::Arc<#live_options_name>,)
+}
+
+// Fake server handle is used as settings in tests, so it gets the default options
+pub fn generate_fake_server_options_impl(struct_name: &Ident) -> TokenStream {
+    let options_settings_trait_name = get_options_settings_trait_name(struct_name);
+    let handle_name =
+        TokenStream::from_str(format!("{}FakeServerHandle", struct_name).as_str()).unwrap();
+
+    quote::quote! {
+        impl #options_settings_trait_name for #handle_name {}
+    }
+}
+
+// Options are read each time GrpcChannel asks for the url, so they are refreshed on every (re)connect.
+// The client built by new() or build() gets them on the first connect, before the first call is sent
+pub fn generate_client_options_types(struct_name: &Ident, vis: &syn::Visibility) -> TokenStream {
+    let options_name = get_client_options_name(struct_name);
+    let options_settings_trait_name = get_options_settings_trait_name(struct_name);
+    let live_options_name = get_live_options_name(struct_name);
+    let options_reader_name = get_options_reader_name(struct_name);
+
+    quote::quote! {
+        #[derive(Clone, Debug, Default)]
+        #vis struct #options_name {
+            pub request_timeout: Option<std::time::Duration>,
+            pub ping_timeout: Option<std::time::Duration>,
+            pub ping_interval: Option<std::time::Duration>,
+            pub retries: Option<usize>,
+        }
+
+        #[async_trait::async_trait]
+        #vis trait #options_settings_trait_name: my_grpc_extensions::GrpcClientSettings {
+            async fn get_grpc_client_options(&self, _name: &'static str) -> #options_name {
+                #options_name::default()
+            }
+        }
+
+        // Ping interval the client is built with is the tick of the ping loop. Last ping goes by the tokio clock,
+        // the same as the loop, so paused time of tests applies to both
+        struct #live_options_name {
+            default_retries: usize,
+            default_request_timeout: std::time::Duration,
+            default_ping_timeout: std::time::Duration,
+            default_ping_interval: std::time::Duration,
+            retries: std::sync::atomic::AtomicUsize,
+            request_timeout_micros: std::sync::atomic::AtomicU64,
+            ping_timeout_micros: std::sync::atomic::AtomicU64,
+            ping_interval_micros: std::sync::atomic::AtomicU64,
+            last_ping: std::sync::Mutex<Option<tokio::time::Instant>>,
+        }
+
+        impl #live_options_name {
+            fn new(
+                retries: usize,
+                request_timeout: std::time::Duration,
+                ping_timeout: std::time::Duration,
+                ping_interval: std::time::Duration,
+            ) -> Self {
+                Self {
+                    default_retries: retries,
+                    default_request_timeout: request_timeout,
+                    default_ping_timeout: ping_timeout,
+                    default_ping_interval: ping_interval,
+                    retries: std::sync::atomic::AtomicUsize::new(retries),
+                    request_timeout_micros: std::sync::atomic::AtomicU64::new(
+                        request_timeout.as_micros() as u64,
+                    ),
+                    ping_timeout_micros: std::sync::atomic::AtomicU64::new(
+                        ping_timeout.as_micros() as u64,
+                    ),
+                    ping_interval_micros: std::sync::atomic::AtomicU64::new(
+                        ping_interval.as_micros() as u64,
+                    ),
+                    last_ping: std::sync::Mutex::new(None),
+                }
+            }
+
+            // Values which can not be applied are logged, since the options are set apart from the code
+            fn warn_not_applied(&self, options: &#options_name) {
+                if let Some(value) = options.request_timeout {
+                    if value > self.default_request_timeout {
+                        tracing::warn!(
+                            "request_timeout {:?} from the options is capped by {:?} the client is built with",
+                            value,
+                            self.default_request_timeout
+                        );
+                    }
+                }
+
+                if let Some(value) = options.ping_timeout {
+                    if value > self.default_ping_timeout {
+                        tracing::warn!(
+                            "ping_timeout {:?} from the options is capped by {:?} the client is built with",
+                            value,
+                            self.default_ping_timeout
+                        );
+                    }
+                }
+
+                if let Some(value) = options.ping_interval {
+                    if value < self.default_ping_interval {
+                        tracing::warn!(
+                            "ping_interval {:?} from the options is raised to {:?} the client is built with",
+                            value,
+                            self.default_ping_interval
+                        );
+                    }
+                }
+            }
+
+            // Values are kept as microseconds in u64, so a bigger one is left out and the default is used
+            fn fits_in_micros(name: &str, value: &std::time::Duration) -> bool {
+                if value.as_micros() > u64::MAX as u128 {
+                    tracing::warn!("{} {:?} from the options is too big and is not applied", name, value);
+                    return false;
+                }
+
+                true
+            }
+
+            fn apply(&self, options: &#options_name) {
+                self.warn_not_applied(options);
+
+                let retries = options.retries.unwrap_or(self.default_retries);
+                let request_timeout = options
+                    .request_timeout
+                    .filter(|value| Self::fits_in_micros("request_timeout", value))
+                    .unwrap_or(self.default_request_timeout);
+                let ping_timeout = options
+                    .ping_timeout
+                    .filter(|value| Self::fits_in_micros("ping_timeout", value))
+                    .unwrap_or(self.default_ping_timeout);
+                let ping_interval = options
+                    .ping_interval
+                    .filter(|value| Self::fits_in_micros("ping_interval", value))
+                    .unwrap_or(self.default_ping_interval);
+
+                self.retries
+                    .store(retries, std::sync::atomic::Ordering::SeqCst);
+                self.request_timeout_micros.store(
+                    request_timeout.as_micros() as u64,
+                    std::sync::atomic::Ordering::SeqCst,
+                );
+                self.ping_timeout_micros.store(
+                    ping_timeout.as_micros() as u64,
+                    std::sync::atomic::Ordering::SeqCst,
+                );
+                self.ping_interval_micros.store(
+                    ping_interval.as_micros() as u64,
+                    std::sync::atomic::Ordering::SeqCst,
+                );
+            }
+
+            fn get_retries(&self) -> usize {
+                self.retries.load(std::sync::atomic::Ordering::SeqCst)
+            }
+
+            fn get_request_timeout(&self) -> std::time::Duration {
+                std::time::Duration::from_micros(
+                    self.request_timeout_micros
+                        .load(std::sync::atomic::Ordering::SeqCst),
+                )
+            }
+
+            fn get_ping_timeout(&self) -> std::time::Duration {
+                std::time::Duration::from_micros(
+                    self.ping_timeout_micros
+                        .load(std::sync::atomic::Ordering::SeqCst),
+                )
+            }
+
+            // Ticks come a bit early or late, so the interval is rounded to the nearest tick
+            fn is_ping_due(&self) -> bool {
+                let ping_interval = std::time::Duration::from_micros(
+                    self.ping_interval_micros
+                        .load(std::sync::atomic::Ordering::SeqCst),
+                );
+
+                let mut last_ping = self.last_ping.lock().unwrap_or_else(|err| err.into_inner());
+
+                if let Some(last_ping) = *last_ping {
+                    if last_ping.elapsed() + self.default_ping_interval / 2 < ping_interval {
+                        return false;
+                    }
+                }
+
+                *last_ping = Some(tokio::time::Instant::now());
+                true
+            }
+        }
+
+        struct #options_reader_name {
+            settings: std::sync::Arc<dyn #options_settings_trait_name + Send + Sync + 'static>,
+            live_options: std::sync::Arc<#live_options_name>,
+        }
+
+        #[async_trait::async_trait]
+        impl my_grpc_extensions::GrpcClientSettings for #options_reader_name {
+            async fn get_grpc_url(&self, name: &'static str) -> String {
+                let options = self.settings.get_grpc_client_options(name).await;
+                self.live_options.apply(&options);
+                self.settings.get_grpc_url(name).await
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::attributes_schema::{check_keys, read_params, CLIENT_SCHEMA};
+
+// Attributes written inline win over [clients.{ClientName}] section of the file, which wins over [defaults] section.
+// Returns merged attributes and the full path of the file, so it can be tracked by cargo
+pub fn apply_config_file(
+    struct_name: &Ident,
+    attr_input: TokenStream,
+) -> Result<(TokenStream, Option<String>), syn::Error> {
+    let attributes = ParamsList::new(attr_input.clone(), || None)?;
+
+    let config_file = match attributes.try_get_named_param("config_file") {
+        Some(value) => value,
+        None => return Ok((attr_input, None)),
+    };
+
+    let file_name = config_file.unwrap_as_string_value()?.as_str();
+
+    let path = match std::fs::canonicalize(file_name) {
+        Ok(path) => path,
+        Err(err) => {
+            return Err(config_file.throw_error(
+                format!("Can not open file: {}. Error: {:?}", file_name, err).as_str(),
+            ))
+        }
+    };
+
+    let table: toml::Table = match std::fs::read_to_string(&path) {
+        Ok(content) => match content.parse() {
+            Ok(table) => table,
+            Err(err) => {
+                return Err(config_file.throw_error(
+                    format!("Can not parse file: {}. Error: {}", file_name, err).as_str(),
+                ))
+            }
+        },
+        Err(err) => {
+            return Err(config_file.throw_error(
+                format!("Can not read file: {}. Error: {:?}", file_name, err).as_str(),
+            ))
+        }
+    };
+
+    let client_name = struct_name.to_string();
+    let mut sections = Vec::new();
+
+    match get_section(&table, "clients") {
+        Ok(Some(clients)) => match get_section(clients, client_name.as_str()) {
+            Ok(Some(client)) => sections.push((format!("clients.{}", client_name), client)),
+            Ok(None) => {}
+            Err(err) => return Err(config_file.throw_error(err.as_str())),
+        },
+        Ok(None) => {}
+        Err(err) => return Err(config_file.throw_error(err.as_str())),
+    }
+
+    match get_section(&table, "defaults") {
+        Ok(Some(defaults)) => sections.push(("defaults".to_string(), defaults)),
+        Ok(None) => {}
+        Err(err) => return Err(config_file.throw_error(err.as_str())),
+    }
+
+    if let Some(name) = table
+        .keys()
+        .find(|name| name.as_str() != "clients" && name.as_str() != "defaults")
+    {
+        return Err(config_file.throw_error(
+            format!(
+                "{}: unknown section [{}]. Only [defaults] and [clients.{{ClientName}}] are supported",
+                file_name, name
+            )
+            .as_str(),
+        ));
+    }
+
+    let mut merged = read_params(&attr_input);
+
+    for (section_name, section) in sections {
+        let section = match section_to_tokens(section) {
+            Ok(section) => section,
+            Err(err) => {
+                return Err(config_file
+                    .throw_error(format!("{} [{}]: {}", file_name, section_name, err).as_str()))
+            }
+        };
+
+        if let Err(err) = check_keys(&section, CLIENT_SCHEMA) {
+            return Err(config_file
+                .throw_error(format!("{} [{}]: {}", file_name, section_name, err).as_str()));
+        }
+
+        for (key, value) in read_params(&section) {
+            if !merged.iter().any(|(merged_key, _)| *merged_key == key) {
+                merged.push((key, value));
+            }
+        }
+    }
+
+    let keys = merged.iter().map(|(key, _)| key);
+    let values = merged.iter().map(|(_, value)| value);
+
+    Ok((
+        quote::quote!(#(#keys: #values),*),
+        Some(path.to_string_lossy().to_string()),
+    ))
+}
+
+// Generated code includes the file, so cargo rebuilds the client once the file is changed
+pub fn generate_track_config_file(path: &str) -> TokenStream {
+    quote::quote! {
+        const _: &[u8] = include_bytes!(#path);
+    }
+}
+
+fn get_section<'s>(table: &'s toml::Table, name: &str) -> Result<Option<&'s toml::Table>, String> {
+    match table.get(name) {
+        Some(toml::Value::Table(section)) => Ok(Some(section)),
+        Some(_) => Err(format!("{} must be a section", name)),
+        None => Ok(None),
+    }
+}
+
+fn section_to_tokens(section: &toml::Table) -> Result<TokenStream, String> {
+    let mut params = Vec::new();
+
+    for (key, value) in section {
+        // metadata = { x-source = "my-app" } in the file is metadata: [{ key: "x-source", value: "my-app" }] inline
+        let value = match (key.as_str(), value) {
+            ("metadata", toml::Value::Table(metadata)) => metadata_to_tokens(metadata)?,
+            _ => value_to_tokens(value)?,
+        };
+
+        let key = to_ident(key)?;
+        params.push(quote::quote!(#key: #value));
+    }
+
+    Ok(quote::quote!(#(#params),*))
+}
+
+fn metadata_to_tokens(metadata: &toml::Table) -> Result<TokenStream, String> {
+    let mut items = Vec::new();
+
+    for (key, value) in metadata {
+        match value {
+            toml::Value::String(value) => items.push(quote::quote!({ key: #key, value: #value })),
+            _ => return Err(format!("metadata value of {} must be a string", key)),
+        }
+    }
+
+    Ok(quote::quote!([#(#items),*]))
+}
+
+fn value_to_tokens(value: &toml::Value) -> Result<TokenStream, String> {
+    match value {
+        toml::Value::String(value) => Ok(quote::quote!(#value)),
+        toml::Value::Integer(value) => {
+            let value = proc_macro2::Literal::i64_unsuffixed(*value);
+            Ok(quote::quote!(#value))
+        }
+        toml::Value::Float(value) => {
+            let value = proc_macro2::Literal::f64_unsuffixed(*value);
+            Ok(quote::quote!(#value))
+        }
+        toml::Value::Boolean(value) => Ok(quote::quote!(#value)),
+        toml::Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(value_to_tokens)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(quote::quote!([#(#items),*]))
+        }
+        toml::Value::Table(table) => {
+            let mut params = Vec::new();
+
+            for (key, value) in table {
+                let key = to_ident(key)?;
+                let value = value_to_tokens(value)?;
+                params.push(quote::quote!(#key: #value));
+            }
+
+            Ok(quote::quote!({ #(#params),* }))
+        }
+        toml::Value::Datetime(value) => Err(format!("dates are not supported: {}", value)),
+    }
+}
+
+fn to_ident(key: &str) -> Result<Ident, String> {
+    match syn::parse_str::<Ident>(key) {
+        Ok(ident) => Ok(ident),
+        Err(_) => Err(format!("{} is not a valid parameter name", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // File goes to the temp dir under the name of the test, so the tests do not share it
+    fn apply_file(
+        test_name: &str,
+        content: &str,
+        attributes: TokenStream,
+    ) -> Result<Vec<(String, String)>, String> {
+        let path = std::env::temp_dir().join(format!("grpc_client_{}.toml", test_name));
+        std::fs::write(&path, content).unwrap();
+
+        let path = path.to_string_lossy().to_string();
+        let struct_name = Ident::new("KeyValueClient", proc_macro2::Span::call_site());
+
+        let attr_input = if attributes.is_empty() {
+            quote::quote!(config_file: #path)
+        } else {
+            quote::quote!(config_file: #path, #attributes)
+        };
+
+        match apply_config_file(&struct_name, attr_input) {
+            Ok((tokens, _)) => Ok(read_params(&tokens)
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    fn get_value(params: &[(String, String)], key: &str) -> Option<String> {
+        params
+            .iter()
+            .find(|(param_key, _)| param_key == key)
+            .map(|(_, value)| value.clone())
+    }
+
+    const CONFIG: &str = r#"
+        [defaults]
+        retries = 1
+        request_timeout = "2s"
+
+        [clients.KeyValueClient]
+        retries = 2
+        ping_interval = "10s"
+    "#;
+
+    #[test]
+    fn test_inline_attributes_win() {
+        let params = apply_file(
+            "inline_attributes_win",
+            CONFIG,
+            quote::quote!(retries: 5, ping_interval: "20s"),
+        )
+        .unwrap();
+
+        assert_eq!(get_value(&params, "retries"), Some("5".to_string()));
+        assert_eq!(
+            get_value(&params, "ping_interval"),
+            Some("\"20s\"".to_string())
+        );
+        assert_eq!(
+            get_value(&params, "request_timeout"),
+            Some("\"2s\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_client_section_wins_over_defaults() {
+        let params = apply_file("client_section_wins", CONFIG, quote::quote!()).unwrap();
+
+        assert_eq!(get_value(&params, "retries"), Some("2".to_string()));
+        assert_eq!(
+            get_value(&params, "ping_interval"),
+            Some("\"10s\"".to_string())
+        );
+        assert_eq!(
+            get_value(&params, "request_timeout"),
+            Some("\"2s\"".to_string())
+        );
+        assert_eq!(params.iter().filter(|(key, _)| key == "retries").count(), 1);
+    }
+
+    #[test]
+    fn test_unknown_key_is_rejected() {
+        let err = apply_file("unknown_key", "[defaults]\nretriez = 1\n", quote::quote!())
+            .err()
+            .unwrap();
+
+        assert!(err.contains("[defaults]"));
+        assert!(err.contains("Unknown parameter retriez"));
+    }
+
+    #[test]
+    fn test_unknown_section_is_rejected() {
+        let err = apply_file(
+            "unknown_section",
+            "[client]\nretries = 1\n",
+            quote::quote!(),
+        )
+        .err()
+        .unwrap();
+
+        assert!(err.contains("unknown section [client]"));
+    }
+
+    #[test]
+    fn test_section_to_tokens() {
+        let table: toml::Table = r#"
+            retries = 2
+            request_timeout = "750ms"
+            metadata = { x-source = "my-app" }
+
+            [[overrides]]
+            fn_name = "Get"
+            hedge = { after = "50ms", max_extra = 1 }
+        "#
+        .parse()
+        .unwrap();
+
+        let tokens = section_to_tokens(&table).unwrap();
+
+        assert!(check_keys(&tokens, CLIENT_SCHEMA).is_ok());
+
+        let params = read_params(&tokens);
+        let keys: Vec<String> = params.iter().map(|(key, _)| key.to_string()).collect();
+
+        assert_eq!(
+            keys,
+            vec!["metadata", "overrides", "request_timeout", "retries"]
+        );
+
+        assert_eq!(
+            params[0].1.to_string(),
+            quote::quote!([{ key: "x-source", value: "my-app" }]).to_string()
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use proc_macro2::TokenStream;
+use types_reader::ParamsList;
+
+const INVALID_DURATION: &str =
+    "Duration must be a number with a unit: \"750ms\", \"2s\", \"1m\" or \"1h\"";
+
+pub fn parse_duration(src: &str) -> Result<Duration, String> {
+    let unit_start = match src.find(|c: char| !c.is_ascii_digit()) {
+        Some(unit_start) if unit_start > 0 => unit_start,
+        _ => return Err(INVALID_DURATION.to_string()),
+    };
+
+    let value: u64 = match src[..unit_start].parse() {
+        Ok(value) => value,
+        Err(_) => return Err(format!("{} is too big", src)),
+    };
+
+    let seconds_in_unit = match &src[unit_start..] {
+        "ms" => return check_micros(src, Duration::from_millis(value)),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return Err(INVALID_DURATION.to_string()),
+    };
+
+    match value.checked_mul(seconds_in_unit) {
+        Some(seconds) => check_micros(src, Duration::from_secs(seconds)),
+        None => Err(format!("{} is too big", src)),
+    }
+}
+
+// Durations go to the generated code and to the live options as microseconds in u64
+fn check_micros(src: &str, duration: Duration) -> Result<Duration, String> {
+    if duration.as_micros() > u64::MAX as u128 {
+        return Err(format!("{} is too big", src));
+    }
+
+    Ok(duration)
+}
+
+// Duration is set as a string with units (request_timeout: "750ms").
+// Old keys with the unit in the name (request_timeout_sec: 5) are still supported
+pub fn try_read_duration(
+    params: &ParamsList,
+    name: &str,
+    legacy_name: &str,
+    from_legacy: fn(u64) -> Duration,
+) -> Result<Option<Duration>, syn::Error> {
+    match (
+        params.try_get_named_param(name),
+        params.try_get_named_param(legacy_name),
+    ) {
+        (Some(value), Some(_)) => Err(value.throw_error(
+            format!("{} and {} can not be used together", name, legacy_name).as_str(),
+        )),
+        (Some(value), None) => match parse_duration(value.unwrap_as_string_value()?.as_str()) {
+            Ok(duration) => Ok(Some(duration)),
+            Err(err) => Err(value.throw_error(format!("Invalid {}. {}", name, err).as_str())),
+        },
+        (None, Some(value)) => {
+            let number = value.unwrap_as_number_value()?.as_usize() as u64;
+
+            match check_micros(&number.to_string(), from_legacy(number)) {
+                Ok(duration) => Ok(Some(duration)),
+                Err(err) => {
+                    Err(value.throw_error(format!("Invalid {}. {}", legacy_name, err).as_str()))
+                }
+            }
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+pub fn read_duration(
+    params: &ParamsList,
+    name: &str,
+    legacy_name: &str,
+    from_legacy: fn(u64) -> Duration,
+) -> Result<Duration, syn::Error> {
+    match try_read_duration(params, name, legacy_name, from_legacy)? {
+        Some(duration) => Ok(duration),
+        None => Err(syn::Error::new_spanned(
+            params.get_token_stream().clone(),
+            format!("{} is required", name),
+        )),
+    }
+}
+
+pub fn duration_to_tokens(duration: Duration) -> TokenStream {
+    let micros = proc_macro2::Literal::u64_unsuffixed(duration.as_micros() as u64);
+    quote::quote!(std::time::Duration::from_micros(#micros))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::parse_duration;
+
+    #[test]
+    fn test_units() {
+        assert_eq!(parse_duration("750ms"), Ok(Duration::from_millis(750)));
+        assert_eq!(parse_duration("2s"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("3m"), Ok(Duration::from_secs(180)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse_duration("750").is_err());
+        assert!(parse_duration("ms").is_err());
+        assert!(parse_duration("1.5s").is_err());
+        assert!(parse_duration("2 s").is_err());
+        assert!(parse_duration("2d").is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(
+            parse_duration("18446744073709551615h"),
+            Err("18446744073709551615h is too big".to_string())
+        );
+        assert_eq!(
+            parse_duration("99999999999999999999s"),
+            Err("99999999999999999999s is too big".to_string())
+        );
+        assert_eq!(
+            parse_duration("18446744073709551615s"),
+            Err("18446744073709551615s is too big".to_string())
+        );
+        assert_eq!(
+            parse_duration("18446744073709s"),
+            Ok(Duration::from_secs(18446744073709))
+        );
+        assert_eq!(
+            parse_duration("18446744073709552ms"),
+            Err("18446744073709552ms is too big".to_string())
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_endpoint_health_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}EndpointHealth", struct_name).as_str()).unwrap()
+}
+
+// Factory keeps health of the endpoint it pings, so endpoints which do not answer pings can be taken out of use
+pub fn generate_factory_struct(struct_name: &Ident, live_options_field: TokenStream) -> TokenStream {
+    let health_name = get_endpoint_health_name(struct_name);
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+
+    quote::quote! {
+        struct MyGrpcServiceFactory {
+            health: std::sync::Arc<#health_name>,
+            shutdown: std::sync::Arc<#shutdown_name>,
+            #live_options_field
+        }
+    }
+}
+
+// Skipped tick of the ping loop keeps the result of the last ping, so a longer ping interval from the options
+// does not make the endpoint silent
+pub fn generate_ping_with_health(
+    on_ping_failure: &TokenStream,
+    with_client_options: bool,
+) -> TokenStream {
+    let stop_ping_on_shutdown = super::shutdown::generate_stop_ping_on_shutdown();
+    let ping_call = super::client_options::generate_ping_call(
+        with_client_options,
+        quote::quote!(self.health.ping_skipped();),
+    );
+
+    quote::quote! {
+        async fn ping(&self, mut service: TGrpcService) {
+            #stop_ping_on_shutdown
+
+            match #ping_call {
+                Ok(_) => self.health.ping_ok(),
+                Err(err) => {
+                    self.health.ping_failed();
+                    #on_ping_failure
+                    panic!("Ping failed: {:?}", err);
+                }
+            }
+        }
+    }
+}
+
+// Endpoint is healthy if its last ping succeeded and happened not longer than max_silence ago.
+// It goes by the tokio clock, the same as the ping loop, so paused time of tests applies to both
+pub fn generate_endpoint_health_struct(struct_name: &Ident) -> TokenStream {
+    let health_name = get_endpoint_health_name(struct_name);
+    let state_name =
+        TokenStream::from_str(format!("{}EndpointHealthState", struct_name).as_str()).unwrap();
+
+    quote::quote! {
+        struct #state_name {
+            last_ping_ok: tokio::time::Instant,
+            healthy_since: tokio::time::Instant,
+            ping_failed: bool,
+        }
+
+        struct #health_name {
+            max_silence: std::time::Duration,
+            state: std::sync::Mutex<#state_name>,
+        }
+
+        impl #health_name {
+            fn new(max_silence: std::time::Duration) -> Self {
+                let now = tokio::time::Instant::now();
+                Self {
+                    max_silence,
+                    state: std::sync::Mutex::new(#state_name {
+                        last_ping_ok: now,
+                        healthy_since: now,
+                        ping_failed: false,
+                    }),
+                }
+            }
+
+            fn ping_ok(&self) {
+                let mut state = self.state.lock().unwrap();
+                let now = tokio::time::Instant::now();
+
+                if state.ping_failed || state.last_ping_ok.elapsed() > self.max_silence {
+                    state.healthy_since = now;
+                }
+
+                state.last_ping_ok = now;
+                state.ping_failed = false;
+            }
+
+            fn ping_skipped(&self) {
+                let mut state = self.state.lock().unwrap();
+
+                if !state.ping_failed && state.last_ping_ok.elapsed() <= self.max_silence {
+                    state.last_ping_ok = tokio::time::Instant::now();
+                }
+            }
+
+            fn ping_failed(&self) {
+                self.state.lock().unwrap().ping_failed = true;
+            }
+
+            fn get_healthy_for(&self) -> Option<std::time::Duration> {
+                let state = self.state.lock().unwrap();
+
+                if state.ping_failed || state.last_ping_ok.elapsed() > self.max_silence {
+                    return None;
+                }
+
+                Some(state.healthy_since.elapsed())
+            }
+
+            fn is_healthy(&self) -> bool {
+                self.get_healthy_for().is_some()
+            }
+        }
+    }
+}
+
+pub fn generate_new_health(struct_name: &Ident, timings: &TokenStream) -> TokenStream {
+    let health_name = get_endpoint_health_name(struct_name);
+
+    quote::quote! {
+        std::sync::Arc::new(#health_name::new(
+            #timings.ping_interval + #timings.ping_timeout * 2,
+        ))
+    }
+}
@@ -0,0 +1,195 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_call_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Call", struct_name).as_str()).unwrap()
+}
+
+// Executor of one attempt of the call: the first one with its retries or a hedge.
+// GrpcChannel calls it for every retry, so attempts are counted by the executor itself.
+// It derefs to the client, so executor code reads the client fields the same way.
+pub fn generate_call_struct(struct_name: &Ident) -> TokenStream {
+    let call_name = get_call_name(struct_name);
+
+    quote::quote! {
+        struct #call_name<'s> {
+            client: &'s #struct_name,
+            attempts: std::sync::atomic::AtomicUsize,
+            is_hedge: bool,
+        }
+
+        impl<'s> #call_name<'s> {
+            fn new(client: &'s #struct_name, is_hedge: bool) -> Self {
+                Self {
+                    client,
+                    attempts: std::sync::atomic::AtomicUsize::new(0),
+                    is_hedge,
+                }
+            }
+
+            fn next_attempt(&self) -> usize {
+                self.attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1
+            }
+
+            fn get_attempt_kind(&self, attempt: usize) -> &'static str {
+                if attempt > 1 {
+                    "retry"
+                } else if self.is_hedge {
+                    "hedge"
+                } else {
+                    "first"
+                }
+            }
+        }
+
+        impl<'s> std::ops::Deref for #call_name<'s> {
+            type Target = #struct_name;
+
+            fn deref(&self) -> &Self::Target {
+                self.client
+            }
+        }
+    }
+}
+
+// Executor traits are implemented for the call struct once attempts are observed, otherwise for the client itself
+// Returns generics of the impl and the type
+pub fn get_executor_type(
+    struct_name: &Ident,
+    with_metrics: bool,
+    with_tracing: bool,
+) -> (TokenStream, TokenStream) {
+    if with_metrics || with_tracing {
+        let call_name = get_call_name(struct_name);
+        (quote::quote!(<'s>), quote::quote!(#call_name<'s>))
+    } else {
+        (quote::quote!(), quote::quote!(#struct_name))
+    }
+}
+
+// Returns the statement which creates the executor and the expression which is passed to GrpcChannel
+pub fn generate_create_executor(
+    struct_name: &Ident,
+    is_hedge: TokenStream,
+    with_metrics: bool,
+    with_tracing: bool,
+) -> (TokenStream, TokenStream) {
+    if with_metrics || with_tracing {
+        let call_name = get_call_name(struct_name);
+        (
+            quote::quote!(let grpc_call = #call_name::new(self, #is_hedge);),
+            quote::quote!(&grpc_call),
+        )
+    } else {
+        (quote::quote!(), quote::quote!(self))
+    }
+}
+
+// Features which change the code of the request executor
+#[derive(Clone, Copy)]
+pub struct ExecutorFeatures {
+    pub with_metrics: bool,
+    pub with_tracing: bool,
+    pub with_trace_propagation: bool,
+    pub with_client_options: bool,
+}
+
+pub fn generate_executor_call(
+    struct_name: &Ident,
+    rpc_name: &str,
+    fn_name: &TokenStream,
+    input_param_invoke: TokenStream,
+    features: ExecutorFeatures,
+) -> TokenStream {
+    let ExecutorFeatures {
+        with_metrics,
+        with_tracing,
+        with_trace_propagation,
+        with_client_options,
+    } = features;
+
+    let insert_traceparent = if with_trace_propagation {
+        super::trace_spans::generate_insert_traceparent()
+    } else {
+        quote::quote!()
+    };
+
+    let service_call = quote::quote! {
+        {
+            let mut request = tonic::Request::new(#input_param_invoke);
+
+            for (key, value) in &self.metadata {
+                request.metadata_mut().insert(key.clone(), value.clone());
+            }
+
+            #insert_traceparent
+
+            service.#fn_name(request)
+        }
+    };
+
+    let service_call = if with_client_options {
+        super::client_options::wrap_with_live_request_timeout(rpc_name, service_call)
+    } else {
+        quote::quote!(#service_call.await)
+    };
+
+    if !with_metrics && !with_tracing {
+        return quote::quote!(let result = #service_call?;);
+    }
+
+    let (count_attempt, count_response) = if with_metrics {
+        let metrics_name = super::metrics::get_metrics_name(struct_name);
+        (
+            quote::quote! {
+                match attempt_kind {
+                    "retry" => #metrics_name::get().retries.with_label_values(&[#rpc_name]).inc(),
+                    "hedge" => #metrics_name::get().hedges.with_label_values(&[#rpc_name]).inc(),
+                    _ => {}
+                }
+            },
+            quote::quote! {
+                let code = match &result {
+                    Ok(_) => tonic::Code::Ok,
+                    Err(status) => status.code(),
+                };
+
+                #metrics_name::get()
+                    .responses
+                    .with_label_values(&[#rpc_name, format!("{:?}", code).as_str(), attempt_kind])
+                    .inc();
+            },
+        )
+    } else {
+        (quote::quote!(), quote::quote!())
+    };
+
+    let trace_attempt = if with_tracing {
+        quote::quote! {
+            tracing::Span::current().record("attempt", attempt);
+
+            match attempt_kind {
+                "retry" => tracing::info!(rpc = #rpc_name, attempt, "Retrying grpc call"),
+                "hedge" => tracing::info!(rpc = #rpc_name, "Hedging grpc call"),
+                _ => {}
+            }
+        }
+    } else {
+        quote::quote!()
+    };
+
+    quote::quote! {
+        let attempt = self.next_attempt();
+        let attempt_kind = self.get_attempt_kind(attempt);
+
+        #trace_attempt
+        #count_attempt
+
+        let result = #service_call;
+
+        #count_response
+
+        let result = result?;
+    }
+}
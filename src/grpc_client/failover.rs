@@ -0,0 +1,231 @@
+use std::{str::FromStr, time::Duration};
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::duration_param::read_duration;
+
+pub struct Failover {
+    pub secondary_service_name: String,
+    pub failback_after: Duration,
+}
+
+impl Failover {
+    pub fn new(attributes: &ParamsList) -> Result<Option<Self>, syn::Error> {
+        let failover = attributes.try_get_named_param("failover");
+
+        if failover.is_none() {
+            return Ok(None);
+        }
+
+        let failover = failover.unwrap().unwrap_as_single_object()?;
+
+        Ok(Some(Self {
+            secondary_service_name: failover
+                .get_named_param("secondary_service_name")?
+                .unwrap_as_string_value()?
+                .to_string(),
+            failback_after: read_duration(
+                failover,
+                "failback_after",
+                "failback_after_sec",
+                Duration::from_secs,
+            )?,
+        }))
+    }
+}
+
+pub fn get_failover_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Failover", struct_name).as_str()).unwrap()
+}
+
+// Calls go to the primary endpoint until it stops answering pings. Then they go to the secondary one
+// until the primary answers pings for failback_after in a row. The primary is probed while calls go to the secondary.
+pub fn generate_failover(
+    struct_name: &Ident,
+    failover: &Failover,
+    observe_connects: bool,
+    with_client_options: bool,
+    extensions_telemetry: bool,
+) -> TokenStream {
+    let timings_name = super::client_builder::get_timings_name(struct_name);
+    let failover_name = get_failover_name(struct_name);
+    let health_name = super::endpoint_health::get_endpoint_health_name(struct_name);
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+    let channel_slot_name = super::shutdown::get_channel_slot_name(struct_name);
+    let secondary_settings_name =
+        TokenStream::from_str(format!("{}SecondarySettings", struct_name).as_str()).unwrap();
+
+    let secondary_service_name = failover.secondary_service_name.as_str();
+    let failback_after = super::duration_param::duration_to_tokens(failover.failback_after);
+
+    let live_options_param = if with_client_options {
+        let live_options_name = super::client_options::get_live_options_name(struct_name);
+        quote::quote!(live_options: std::sync::Arc<#live_options_name>,)
+    } else {
+        quote::quote!()
+    };
+
+    let get_channel = if extensions_telemetry {
+        quote::quote!(get_channel(&my_telemetry::MyTelemetryContext::new()))
+    } else {
+        quote::quote!(get_channel())
+    };
+
+    let new_health = super::endpoint_health::generate_new_health(struct_name, &quote::quote!(timings));
+
+    let primary_settings =
+        super::observed_settings::wrap_settings(struct_name, quote::quote!(settings.clone()), observe_connects);
+
+    let secondary_settings = super::observed_settings::wrap_settings(
+        struct_name,
+        quote::quote!(std::sync::Arc::new(#secondary_settings_name { settings })),
+        observe_connects,
+    );
+
+    let get_factory_fields = |health: TokenStream| {
+        let mut result = vec![(quote::quote!(health), health), (quote::quote!(shutdown), quote::quote!(shutdown))];
+
+        if with_client_options {
+            result.push((quote::quote!(live_options), quote::quote!(live_options)));
+        }
+
+        result
+    };
+
+    let new_primary = super::shutdown::generate_new_channel_slot(
+        struct_name,
+        &primary_settings,
+        &get_factory_fields(quote::quote!(primary_health)),
+        &quote::quote!(timings),
+    );
+
+    let new_secondary = super::shutdown::generate_new_channel_slot(
+        struct_name,
+        &secondary_settings,
+        &get_factory_fields(quote::quote!(secondary_health)),
+        &quote::quote!(timings),
+    );
+
+    quote::quote! {
+        struct #secondary_settings_name {
+            settings: std::sync::Arc<dyn my_grpc_extensions::GrpcClientSettings + Send + Sync + 'static>,
+        }
+
+        #[async_trait::async_trait]
+        impl my_grpc_extensions::GrpcClientSettings for #secondary_settings_name {
+            async fn get_grpc_url(&self, _: &'static str) -> String {
+                self.settings.get_grpc_url(#secondary_service_name).await
+            }
+        }
+
+        struct #failover_name {
+            primary: #channel_slot_name,
+            primary_health: std::sync::Arc<#health_name>,
+            secondary: #channel_slot_name,
+            on_secondary: std::sync::atomic::AtomicBool,
+            probe_interval: std::time::Duration,
+            last_probe: std::sync::Mutex<Option<tokio::time::Instant>>,
+            shutdown: std::sync::Arc<#shutdown_name>,
+        }
+
+        impl #failover_name {
+            fn new(
+                settings: std::sync::Arc<dyn my_grpc_extensions::GrpcClientSettings + Send + Sync + 'static>,
+                timings: #timings_name,
+                shutdown: std::sync::Arc<#shutdown_name>,
+                #live_options_param
+            ) -> Self {
+                let primary_health = #new_health;
+                let secondary_health = #new_health;
+
+                Self {
+                    primary: #new_primary,
+                    primary_health,
+                    secondary: #new_secondary,
+                    on_secondary: std::sync::atomic::AtomicBool::new(false),
+                    probe_interval: timings.ping_interval,
+                    last_probe: std::sync::Mutex::new(None),
+                    shutdown,
+                }
+            }
+
+            // Calls do not go to the primary while the client is on the secondary, so the primary channel
+            // dropped by a failed ping is connected again in the background. Its ping loop brings the health back
+            fn probe_primary(&self) {
+                if self.shutdown.is_shut_down() {
+                    return;
+                }
+
+                {
+                    let mut last_probe = self.last_probe.lock().unwrap_or_else(|err| err.into_inner());
+
+                    if let Some(last_probe) = *last_probe {
+                        if last_probe.elapsed() < self.probe_interval {
+                            return;
+                        }
+                    }
+
+                    *last_probe = Some(tokio::time::Instant::now());
+                }
+
+                let primary = match self.primary.get() {
+                    Some(primary) => primary,
+                    None => return,
+                };
+
+                tokio::spawn(async move {
+                    let _ = primary.#get_channel.await;
+                });
+            }
+
+            fn is_on_secondary(&self) -> bool {
+                let on_secondary = self.on_secondary.load(std::sync::atomic::Ordering::SeqCst);
+
+                let use_secondary = if on_secondary {
+                    match self.primary_health.get_healthy_for() {
+                        Some(healthy_for) => healthy_for < #failback_after,
+                        None => true,
+                    }
+                } else {
+                    !self.primary_health.is_healthy()
+                };
+
+                if use_secondary != on_secondary {
+                    self.on_secondary.store(use_secondary, std::sync::atomic::Ordering::SeqCst);
+                }
+
+                use_secondary
+            }
+
+            fn get_active(
+                &self,
+            ) -> Option<std::sync::Arc<my_grpc_extensions::GrpcChannel<TGrpcService>>> {
+                if self.is_on_secondary() {
+                    self.probe_primary();
+                    self.secondary.get()
+                } else {
+                    self.primary.get()
+                }
+            }
+
+            fn reconnect(&self) {
+                self.primary.reconnect();
+                self.secondary.reconnect();
+            }
+
+            fn close(&self) {
+                self.primary.close();
+                self.secondary.close();
+            }
+
+            fn get_active_service_name(&self) -> &'static str {
+                if self.is_on_secondary() {
+                    #secondary_service_name
+                } else {
+                    #struct_name::get_service_name()
+                }
+            }
+        }
+    }
+}
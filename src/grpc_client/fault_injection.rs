@@ -0,0 +1,136 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_fault_injector_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}FaultInjector", struct_name).as_str()).unwrap()
+}
+
+pub fn generate_inject_fault(rpc_name: &str) -> TokenStream {
+    quote::quote!(self.fault_injector.inject(#rpc_name, || self.reconnect_channels()).await?;)
+}
+
+// Faults are injected inside the executor, so they go through the retry and reconnect logic of GrpcChannel.
+// Probability is applied deterministically: with probability 0.25 exactly every 4th call gets the fault.
+// Fields are private, so a fault can only be made by new, which checks the probability.
+// Disconnect replaces the channels of the client, so the next call connects again the same way it does after a lost connection.
+pub fn generate_fault_injector_struct(struct_name: &Ident, vis: &syn::Visibility) -> TokenStream {
+    let injector_name = get_fault_injector_name(struct_name);
+    let fault_name = TokenStream::from_str(format!("{}Fault", struct_name).as_str()).unwrap();
+
+    quote::quote! {
+        #[derive(Clone, Debug)]
+        #vis struct #fault_name {
+            probability: f64,
+            latency: Option<std::time::Duration>,
+            status_code: Option<tonic::Code>,
+            disconnect: bool,
+        }
+
+        impl #fault_name {
+            pub fn new(probability: f64) -> Result<Self, String> {
+                if !(0.0..=1.0).contains(&probability) {
+                    return Err(format!(
+                        "Fault probability must be within 0..=1. Got: {}",
+                        probability
+                    ));
+                }
+
+                Ok(Self {
+                    probability,
+                    latency: None,
+                    status_code: None,
+                    disconnect: false,
+                })
+            }
+
+            pub fn with_latency(mut self, latency: std::time::Duration) -> Self {
+                self.latency = Some(latency);
+                self
+            }
+
+            pub fn with_status_code(mut self, status_code: tonic::Code) -> Self {
+                self.status_code = Some(status_code);
+                self
+            }
+
+            pub fn with_disconnect(mut self) -> Self {
+                self.disconnect = true;
+                self
+            }
+        }
+
+        #vis struct #injector_name {
+            faults: std::sync::Mutex<std::collections::HashMap<String, (#fault_name, u64)>>,
+        }
+
+        impl #injector_name {
+            fn new() -> Self {
+                Self {
+                    faults: std::sync::Mutex::new(std::collections::HashMap::new()),
+                }
+            }
+
+            pub fn set_fault(&self, rpc_name: &str, fault: #fault_name) {
+                self.faults
+                    .lock()
+                    .unwrap()
+                    .insert(rpc_name.to_string(), (fault, 0));
+            }
+
+            pub fn clear_fault(&self, rpc_name: &str) {
+                self.faults.lock().unwrap().remove(rpc_name);
+            }
+
+            pub fn clear_all(&self) {
+                self.faults.lock().unwrap().clear();
+            }
+
+            async fn inject(
+                &self,
+                rpc_name: &str,
+                reconnect: impl FnOnce(),
+            ) -> Result<(), tonic::Status> {
+                let fault = {
+                    let mut faults = self.faults.lock().unwrap();
+
+                    match faults.get_mut(rpc_name) {
+                        Some((fault, calls)) => {
+                            let call_no = *calls as f64;
+                            *calls += 1;
+
+                            if ((call_no + 1.0) * fault.probability).floor()
+                                > (call_no * fault.probability).floor()
+                            {
+                                Some(fault.clone())
+                            } else {
+                                None
+                            }
+                        }
+                        None => None,
+                    }
+                };
+
+                let fault = match fault {
+                    Some(fault) => fault,
+                    None => return Ok(()),
+                };
+
+                if let Some(latency) = fault.latency {
+                    tokio::time::sleep(latency).await;
+                }
+
+                if fault.disconnect {
+                    reconnect();
+                    return Err(tonic::Status::unavailable("Fault injection: disconnect"));
+                }
+
+                if let Some(status_code) = fault.status_code {
+                    return Err(tonic::Status::new(status_code, "Fault injection"));
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
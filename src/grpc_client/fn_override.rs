@@ -1,10 +1,63 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use proc_macro2::TokenStream;
 use types_reader::ParamsList;
 
+use super::{
+    call_limits::CallLimits, duration_param::read_duration, payload_logging::PayloadLogging,
+    response_cache::CacheOverride,
+};
+
+pub struct HedgeOverride {
+    pub after: Duration,
+    pub max_extra: usize,
+}
+
+impl HedgeOverride {
+    pub fn new(item: &ParamsList) -> Result<Option<Self>, syn::Error> {
+        let hedge = item.try_get_named_param("hedge");
+
+        if hedge.is_none() {
+            return Ok(None);
+        }
+
+        let hedge = hedge.unwrap().unwrap_as_single_object()?;
+
+        let result = Self {
+            after: read_duration(hedge, "after", "after_ms", Duration::from_millis)?,
+            max_extra: hedge
+                .get_named_param("max_extra")?
+                .unwrap_as_number_value()?
+                .as_usize(),
+        };
+
+        if result.after.is_zero() {
+            return Err(syn::Error::new_spanned(
+                hedge.get_token_stream().clone(),
+                "after must be greater than 0",
+            ));
+        }
+
+        if result.max_extra == 0 {
+            return Err(syn::Error::new_spanned(
+                hedge.get_token_stream().clone(),
+                "max_extra must be greater than 0",
+            ));
+        }
+
+        Ok(Some(result))
+    }
+}
+
 pub struct FnOverride<'s> {
-    pub retries: usize,
+    pub retries: Option<usize>,
+    pub hedge: Option<HedgeOverride>,
+    pub limits: CallLimits,
+    pub cache: Option<CacheOverride>,
+    pub single_flight: bool,
+    pub log_payloads: Option<PayloadLogging>,
+    pub rename_to: Option<String>,
+    pub visibility: Option<syn::Visibility>,
     pub token_stream: &'s TokenStream,
 }
 
@@ -27,13 +80,78 @@ impl<'s> FnOverride<'s> {
                 .get_named_param("fn_name")?
                 .unwrap_as_string_value()?
                 .to_string();
+
+            let retries = match item.try_get_named_param("retries") {
+                Some(value) => Some(value.unwrap_as_number_value()?.as_usize()),
+                None => None,
+            };
+
+            let idempotent = match item.try_get_named_param("idempotent") {
+                Some(value) => value.unwrap_as_bool_value()?.get_value(),
+                None => true,
+            };
+
+            let hedge = HedgeOverride::new(item)?;
+
+            if hedge.is_some() && !idempotent {
+                return Err(syn::Error::new_spanned(
+                    item.get_token_stream().clone(),
+                    format!(
+                        "Method {} is marked as non-idempotent and can not be hedged",
+                        name
+                    ),
+                ));
+            }
+
+            let cache = CacheOverride::new(item)?;
+
+            if cache.is_some() && !idempotent {
+                return Err(syn::Error::new_spanned(
+                    item.get_token_stream().clone(),
+                    format!(
+                        "Method {} is marked as non-idempotent and can not be cached",
+                        name
+                    ),
+                ));
+            }
+
+            let single_flight = match item.try_get_named_param("single_flight") {
+                Some(value) => value.unwrap_as_bool_value()?.get_value(),
+                None => false,
+            };
+
+            let rename_to = match item.try_get_named_param("rename_to") {
+                Some(value) => {
+                    let rename_to = value.unwrap_as_string_value()?.to_string();
+
+                    if syn::parse_str::<syn::Ident>(rename_to.as_str()).is_err() {
+                        return Err(value.throw_error("rename_to must be a valid rust identifier"));
+                    }
+
+                    Some(rename_to)
+                }
+                None => None,
+            };
+
+            let visibility = match item.try_get_named_param("visibility") {
+                Some(value) => match syn::parse_str::<syn::Visibility>(value.unwrap_as_string_value()?.as_str()) {
+                    Ok(visibility) => Some(visibility),
+                    Err(_) => return Err(value.throw_error("visibility must be a rust visibility like \"pub\", \"pub(crate)\" or \"\" for private")),
+                },
+                None => None,
+            };
+
             result.insert(
                 name,
                 FnOverride {
-                    retries: item
-                        .get_named_param("retries")?
-                        .unwrap_as_number_value()?
-                        .as_usize(),
+                    retries,
+                    hedge,
+                    limits: CallLimits::new(item)?,
+                    cache,
+                    single_flight,
+                    log_payloads: PayloadLogging::try_read(item)?,
+                    rename_to,
+                    visibility,
                     token_stream: item.get_token_stream(),
                 },
             );
@@ -46,3 +164,77 @@ impl<'s> FnOverride<'s> {
         Ok(result)
     }
 }
+
+pub fn get_fn_visibility<'s>(
+    overrides: &'s HashMap<String, FnOverride<'_>>,
+    rpc_name: &str,
+    vis: &'s syn::Visibility,
+) -> &'s syn::Visibility {
+    match overrides.get(rpc_name).and_then(|value| value.visibility.as_ref()) {
+        Some(value) => value,
+        None => vis,
+    }
+}
+
+// Methods of a trait are as visible as the trait, so methods with a narrower visibility
+// are left out of the Api trait and the mock, otherwise they could be reached through them
+pub fn is_hidden_from_api(
+    overrides: &HashMap<String, FnOverride<'_>>,
+    rpc_name: &str,
+    vis: &syn::Visibility,
+) -> bool {
+    let fn_vis = get_fn_visibility(overrides, rpc_name, vis);
+    is_less_visible(fn_vis, vis)
+}
+
+// pub(super) and pub(in path) are ranked the same, since they can not be compared without knowing the module tree.
+// A method is hidden only if it is surely less visible than the struct
+fn get_visibility_rank(vis: &syn::Visibility) -> u8 {
+    match vis {
+        syn::Visibility::Public(_) => 3,
+        syn::Visibility::Restricted(restricted) => {
+            if restricted.path.is_ident("crate") {
+                2
+            } else if restricted.path.is_ident("self") {
+                0
+            } else {
+                1
+            }
+        }
+        syn::Visibility::Inherited => 0,
+    }
+}
+
+fn is_less_visible(fn_vis: &syn::Visibility, vis: &syn::Visibility) -> bool {
+    get_visibility_rank(fn_vis) < get_visibility_rank(vis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_less_visible;
+
+    fn is_less(fn_vis: &str, vis: &str) -> bool {
+        is_less_visible(
+            &syn::parse_str(fn_vis).unwrap(),
+            &syn::parse_str(vis).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_less_visible() {
+        assert!(is_less("pub(crate)", "pub"));
+        assert!(is_less("pub(super)", "pub(crate)"));
+        assert!(is_less("", "pub(super)"));
+        assert!(is_less("pub(self)", "pub(crate)"));
+    }
+
+    #[test]
+    fn test_not_less_visible() {
+        assert!(!is_less("pub", "pub(crate)"));
+        assert!(!is_less("pub(crate)", "pub(crate)"));
+        assert!(!is_less("pub(in crate)", "pub(crate)"));
+        assert!(!is_less("pub(super)", "pub(in crate::grpc)"));
+        assert!(!is_less("pub(crate)", ""));
+        assert!(!is_less("pub(self)", ""));
+    }
+}
@@ -1,42 +1,77 @@
 
 
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
 use proc_macro::TokenStream;
 use types_reader::ParamsList;
 
 
-use crate::grpc_client::{fn_override::FnOverride, proto_file_reader::into_snake_case};
+use crate::grpc_client::{
+    call_limits::CallLimits, client_builder::BuilderDefaults, client_fields::ClientFields, fn_override::FnOverride,
+    attributes_schema::{DEFAULT_PING_INTERVAL, DEFAULT_PING_TIMEOUT, DEFAULT_REQUEST_TIMEOUT, DEFAULT_RETRIES},
+    duration_param::try_read_duration,
+    channel_source::ChannelSource, executor_call::ExecutorFeatures, failover::Failover, load_balancing::LoadBalancing,
+    payload_logging::{PayloadLogging, PayloadLoggingSettings}, proto_file_reader::into_snake_case,
+};
 
-use super::proto_file_reader::ProtoServiceDescription;
+use super::{proto_file_reader::ProtoServiceDescription, MethodsContext};
 
 pub fn generate(
     attr: TokenStream,
     input: TokenStream,
     with_telemetry: bool,
+    with_fault_injection: bool,
+    with_metrics: bool,
+    with_tracing: bool,
+    with_trace_propagation: bool,
 ) -> Result<proc_macro::TokenStream, syn::Error> {
 
-    let ast: syn::DeriveInput = syn::parse(input).unwrap();
+    let ast: syn::DeriveInput = syn::parse(input)?;
 
     let struct_name = &ast.ident;
+    let vis = &ast.vis;
+    let struct_attrs = &ast.attrs;
 
 
 
     let attr_input: proc_macro2::TokenStream = attr.into();
 
+    let (attr_input, config_file_path) = super::config_file::apply_config_file(struct_name, attr_input)?;
+
+    super::attributes_schema::check_keys(&attr_input, super::attributes_schema::CLIENT_SCHEMA)?;
     
     let attributes = ParamsList::new(attr_input, ||None)?;
 
-    let timeout_sec = attributes.get_named_param("request_timeout_sec")?;
-    let timeout_sec = timeout_sec.unwrap_as_number_value()?.as_literal();
+    // Signatures of GrpcChannel::get_channel and create_service are chosen by with-telemetry feature of my-grpc-extensions,
+    // so the feature of this crate has to match it. Attribute only chooses whether generated methods take ctx.
+    // Without it methods keep the plain signatures, so turning the feature on does not break the callers
+    let extensions_telemetry = with_telemetry;
+
+    let with_telemetry = match attributes.try_get_named_param("telemetry") {
+        Some(value) => {
+            let result = value.unwrap_as_bool_value()?.get_value();
+
+            if result && !extensions_telemetry {
+                return Err(value.throw_error(
+                    "telemetry: true requires with-telemetry feature of my-grpc-client-macros and my-grpc-extensions",
+                ));
+            }
+
+            result
+        }
+        None => false,
+    };
 
+    let request_timeout = try_read_duration(&attributes, "request_timeout", "request_timeout_sec", Duration::from_secs)?
+        .unwrap_or(DEFAULT_REQUEST_TIMEOUT);
 
-    let ping_timeout_sec = attributes.get_named_param("ping_timeout_sec")?;
-    let ping_timeout_sec = ping_timeout_sec.unwrap_as_number_value()?.as_literal();
+    let ping_timeout = try_read_duration(&attributes, "ping_timeout", "ping_timeout_sec", Duration::from_secs)?
+        .unwrap_or(DEFAULT_PING_TIMEOUT);
 
+    let ping_interval = try_read_duration(&attributes, "ping_interval", "ping_interval_sec", Duration::from_secs)?
+        .unwrap_or(DEFAULT_PING_INTERVAL);
 
-    let ping_interval_sec = attributes.get_named_param("ping_interval_sec")?;
-    let ping_interval_sec = ping_interval_sec.unwrap_as_number_value()?.as_literal();
+    check_timings(&attributes, request_timeout, ping_timeout, ping_interval)?;
 
     let proto_file = attributes.get_named_param("proto_file")?;
     let proto_file = proto_file.unwrap_as_string_value()?.as_str();
@@ -45,10 +80,10 @@ pub fn generate(
     let grpc_service_name = &proto_file.service_name;
     let grpc_service_name_token = proto_file.get_service_name_as_token();
 
-    let interfaces = super::generate_interfaces_implementations(struct_name, &proto_file);
-
-    let retries = attributes.get_named_param("retries")?;
-    let retries = retries.unwrap_as_number_value()?.as_usize();
+    let retries = match attributes.try_get_named_param("retries") {
+        Some(value) => value.unwrap_as_number_value()?.as_usize(),
+        None => DEFAULT_RETRIES,
+    };
 
 
     let overrides = FnOverride::new(&attributes)?;
@@ -61,6 +96,18 @@ pub fn generate(
     let ns_of_client = format!("use {}::{}::{}", crate_ns,into_snake_case(&grpc_service_name), grpc_service_name);
     use_name_spaces.push(proc_macro2::TokenStream::from_str(ns_of_client.as_str()).unwrap());
 
+    let with_fake_server = match attributes.try_get_named_param("fake_server") {
+        Some(value) => value.unwrap_as_bool_value()?.get_value(),
+        None => false,
+    };
+
+    let fake_server = if with_fake_server {
+        super::generate_fake_server::generate_fake_server(struct_name, vis, &proto_file, crate_ns)
+    } else {
+        quote::quote!()
+    };
+
+
 
     let settings_service_name = if let Some(service_name) =  attributes.try_get_named_param("service_name"){
         service_name.unwrap_as_string_value()?.as_str().to_string()
@@ -78,12 +125,350 @@ pub fn generate(
                 format!("Method {} is not found in proto file for service {}", override_fn_name, grpc_service_name),
             ));
         }
+
+        if fn_override.cache.is_some() && !proto_file.get_method(override_fn_name).unwrap().is_unary(){
+            return Err(syn::Error::new_spanned(
+                fn_override.token_stream.clone(),
+                format!("Method {} is streaming. Only unary methods can be cached", override_fn_name),
+            ));
+        }
+
+        if fn_override.single_flight && !proto_file.get_method(override_fn_name).unwrap().is_unary(){
+            return Err(syn::Error::new_spanned(
+                fn_override.token_stream.clone(),
+                format!("Method {} is streaming. Only unary methods can be single flight", override_fn_name),
+            ));
+        }
     }
     
-    let grpc_methods = super::generate_grpc_methods(&proto_file, retries, &overrides, with_telemetry);
+    let client_proto = super::method_selection::select_methods(&attributes, &proto_file, &overrides)?;
+
+    let client_api = super::generate_client_api::generate_client_api(struct_name, vis, &client_proto, &overrides, with_telemetry);
+
+    let with_mock = match attributes.try_get_named_param("mock") {
+        Some(value) => value.unwrap_as_bool_value()?.get_value(),
+        None => false,
+    };
+
+    let client_mock = if with_mock {
+        super::generate_client_mock::generate_client_mock(struct_name, vis, &client_proto, &overrides, with_telemetry)
+    } else {
+        quote::quote!()
+    };
+
+    let with_blocking = match attributes.try_get_named_param("blocking") {
+        Some(value) => value.unwrap_as_bool_value()?.get_value(),
+        None => false,
+    };
+
+    let blocking_client = if with_blocking {
+        super::generate_blocking_client::generate_blocking_client(struct_name, vis, &client_proto, &overrides, with_telemetry)
+    } else {
+        quote::quote!()
+    };
+
+    let client_limits = CallLimits::new(&attributes)?;
+    let limiter_name = super::call_limits::get_rate_limiter_name(struct_name);
+
+    let mut client_fields = ClientFields::default();
+    ClientFields::check_struct_derives(&ast)?;
+    client_fields.add_user_fields(&ast)?;
+
+    let with_client_options = match attributes.try_get_named_param("client_options") {
+        Some(value) => value.unwrap_as_bool_value()?.get_value(),
+        None => false,
+    };
+
+    let executor_features = ExecutorFeatures {
+        with_metrics,
+        with_tracing,
+        with_trace_propagation,
+        with_client_options,
+    };
+
+    let interfaces = super::generate_interfaces_implementations(struct_name, &client_proto, with_fault_injection, executor_features);
+
+    super::client_builder::add_builder_fields(struct_name, &mut client_fields, with_client_options);
+    client_limits.add_fields("", &limiter_name, &mut client_fields);
+
+    let load_balancing = LoadBalancing::new(&attributes)?;
+    let failover = Failover::new(&attributes)?;
+
+    let channel_source = match (&load_balancing, &failover) {
+        (Some(_), Some(_)) => {
+            return Err(attributes.get_named_param("failover")?.throw_error("failover can not be used together with load_balancing"));
+        }
+        (Some(_), None) => ChannelSource::LoadBalancing,
+        (None, Some(_)) => ChannelSource::Failover,
+        (None, None) => ChannelSource::Single,
+    };
+
+    if with_client_options && load_balancing.is_some() {
+        return Err(attributes.get_named_param("client_options")?.throw_error("client_options can not be used together with load_balancing"));
+    }
+
+    let payload_logging = PayloadLoggingSettings::new(&attributes)?;
+
+    let with_record_replay = match attributes.try_get_named_param("record_replay") {
+        Some(value) => value.unwrap_as_bool_value()?.get_value(),
+        None => false,
+    };
+
+    let methods_context = MethodsContext {
+        struct_name,
+        vis,
+        proto_file: &client_proto,
+        overrides: &overrides,
+        client_limits: &client_limits,
+        payload_logging: &payload_logging,
+        channel_source: &channel_source,
+        with_record_replay,
+        with_client_options,
+        with_metrics,
+        with_tracing,
+        with_telemetry,
+        extensions_telemetry,
+    };
+
+    let grpc_methods = super::generate_grpc_methods(&methods_context, &mut client_fields);
+
+    let (fault_injector_struct, fn_get_fault_injector) = if with_fault_injection {
+        let fault_injector_name = super::fault_injection::get_fault_injector_name(struct_name);
+        let reconnect_channels = channel_source.generate_reconnect_channels();
+
+        client_fields.add(
+            &quote::quote!(fault_injector),
+            quote::quote!(#fault_injector_name),
+            quote::quote!(#fault_injector_name::new()),
+        );
+
+        (
+            super::fault_injection::generate_fault_injector_struct(struct_name, vis),
+            quote::quote! {
+                #vis fn get_fault_injector(&self) -> &#fault_injector_name {
+                    &self.fault_injector
+                }
+
+                fn reconnect_channels(&self) {
+                    #reconnect_channels
+                }
+            },
+        )
+    } else {
+        (quote::quote!(), quote::quote!())
+    };
+
+    let record_replay_struct = if with_record_replay {
+        super::record_replay::generate_record_replay_struct(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let uses_rate_limiter = client_limits.rate_limit.is_some()
+        || overrides.values().any(|fn_override| fn_override.limits.rate_limit.is_some());
+
+    let rate_limiter_struct = if uses_rate_limiter {
+        super::call_limits::generate_rate_limiter_struct(&limiter_name)
+    } else {
+        quote::quote!()
+    };
+
+    let response_cache_struct = if overrides.values().any(|fn_override| fn_override.cache.is_some()) {
+        super::response_cache::generate_response_cache_struct(&super::response_cache::get_response_cache_name(struct_name))
+    } else {
+        quote::quote!()
+    };
+
+    let single_flight_struct = if overrides.values().any(|fn_override| fn_override.single_flight) {
+        super::single_flight::generate_single_flight_struct(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let logs_payloads = payload_logging.verbosity == PayloadLogging::Full
+        || overrides.values().any(|fn_override| fn_override.log_payloads == Some(PayloadLogging::Full));
 
+    let payload_logger_struct = if logs_payloads {
+        super::payload_logging::generate_payload_logger_struct(struct_name, &payload_logging)
+    } else {
+        quote::quote!()
+    };
+
+    let metrics_struct = if with_metrics {
+        super::metrics::generate_metrics_struct(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let observe_connects = with_metrics || with_tracing;
+
+    let observability_types = if observe_connects {
+        let call_struct = super::executor_call::generate_call_struct(struct_name);
+        let observed_settings = super::observed_settings::generate_observed_settings(struct_name, with_metrics, with_tracing);
+        quote::quote!(#call_struct #observed_settings)
+    } else {
+        quote::quote!()
+    };
+
+    let count_ping_failure = if with_metrics {
+        super::metrics::generate_count_ping_failure(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let trace_ping_failure = if with_tracing {
+        super::trace_spans::generate_trace_ping_failure(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let on_ping_failure = quote::quote!(#count_ping_failure #trace_ping_failure);
+
+    let (client_options_types, settings, grpc_client_settings_type) = if with_client_options {
+        let options_settings_trait_name = super::client_options::get_options_settings_trait_name(struct_name);
+        let options_reader_name = super::client_options::get_options_reader_name(struct_name);
+
+        let fake_server_options_impl = if with_fake_server {
+            super::client_options::generate_fake_server_options_impl(struct_name)
+        } else {
+            quote::quote!()
+        };
+
+        let client_options_types = super::client_options::generate_client_options_types(struct_name, vis);
+
+        (
+            quote::quote!(#client_options_types #fake_server_options_impl),
+            quote::quote!(std::sync::Arc::new(#options_reader_name { settings: self.settings, live_options: live_options.clone() })),
+            quote::quote!(std::sync::Arc<dyn #options_settings_trait_name + Send + Sync + 'static>),
+        )
+    } else {
+        (
+            quote::quote!(),
+            quote::quote!(self.settings),
+            quote::quote!(std::sync::Arc<dyn my_grpc_extensions::GrpcClientSettings + Send + Sync + 'static>),
+        )
+    };
+
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+
+    let (factory_struct, fn_ping, channel_types, channel_field, settings_type, channel_initializer, channel_methods) = match (&load_balancing, &failover) {
+        (Some(load_balancing), _) => {
+            let settings_trait_name = super::load_balancing::get_endpoints_settings_trait_name(struct_name);
+            let pool_name = super::load_balancing::get_endpoints_pool_name(struct_name);
+
+            let health_struct = super::endpoint_health::generate_endpoint_health_struct(struct_name);
+            let load_balancing_types = super::load_balancing::generate_load_balancing(struct_name, vis, load_balancing, observe_connects, extensions_telemetry);
+
+            (
+                super::endpoint_health::generate_factory_struct(struct_name, quote::quote!()),
+                super::endpoint_health::generate_ping_with_health(&on_ping_failure, false),
+                quote::quote!(#health_struct #load_balancing_types),
+                quote::quote!(endpoints: #pool_name),
+                quote::quote!(std::sync::Arc<dyn #settings_trait_name + Send + Sync + 'static>),
+                quote::quote!(endpoints: #pool_name::new(self.settings, self.timings, shutdown.clone())),
+                quote::quote!(),
+            )
+        }
+        (None, Some(failover)) => {
+            let failover_name = super::failover::get_failover_name(struct_name);
+
+            let health_struct = super::endpoint_health::generate_endpoint_health_struct(struct_name);
+            let live_options_field = super::client_options::generate_factory_live_options_field(struct_name, with_client_options);
+            let live_options_arg = if with_client_options { quote::quote!(live_options.clone()) } else { quote::quote!() };
+            let channel_slot = super::shutdown::generate_channel_slot(struct_name);
+            let failover_types = super::failover::generate_failover(struct_name, failover, observe_connects, with_client_options, extensions_telemetry);
+
+            (
+                super::endpoint_health::generate_factory_struct(struct_name, live_options_field),
+                super::endpoint_health::generate_ping_with_health(&on_ping_failure, with_client_options),
+                quote::quote!(#health_struct #channel_slot #failover_types),
+                quote::quote!(failover: #failover_name),
+                grpc_client_settings_type,
+                quote::quote!(failover: #failover_name::new(#settings, self.timings, shutdown.clone(), #live_options_arg)),
+                quote::quote! {
+                    #vis fn get_active_service_name(&self) -> &'static str {
+                        self.failover.get_active_service_name()
+                    }
+                },
+            )
+        }
+        (None, None) => {
+            let stop_ping_on_shutdown = super::shutdown::generate_stop_ping_on_shutdown();
+
+            let ping_call = super::client_options::generate_ping_call(with_client_options, quote::quote!());
+
+            let fn_ping = if observe_connects {
+                quote::quote! {
+                    async fn ping(&self, mut service: TGrpcService) {
+                        #stop_ping_on_shutdown
+
+                        if let Err(err) = #ping_call {
+                            #on_ping_failure
+                            panic!("Ping failed: {:?}", err);
+                        }
+                    }
+                }
+            } else {
+                quote::quote! {
+                    async fn ping(&self, mut service: TGrpcService) {
+                        #stop_ping_on_shutdown
+
+                        let result = #ping_call;
+                        result.unwrap();
+                    }
+                }
+            };
+
+            let live_options_field = super::client_options::generate_factory_live_options_field(struct_name, with_client_options);
+
+            let settings = super::observed_settings::wrap_settings(struct_name, settings, observe_connects);
+            let channel_slot_name = super::shutdown::get_channel_slot_name(struct_name);
+
+            let mut factory_fields = vec![(quote::quote!(shutdown), quote::quote!(shutdown))];
+
+            if with_client_options {
+                factory_fields.push((quote::quote!(live_options), quote::quote!(live_options)));
+            }
 
-    let fn_create_service = if with_telemetry{
+            let new_channel_slot = super::shutdown::generate_new_channel_slot(struct_name, &settings, &factory_fields, &quote::quote!(self.timings));
+
+            (
+                quote::quote! {
+                    struct MyGrpcServiceFactory {
+                        shutdown: std::sync::Arc<#shutdown_name>,
+                        #live_options_field
+                    }
+                },
+                fn_ping,
+                super::shutdown::generate_channel_slot(struct_name),
+                quote::quote!(channel: #channel_slot_name),
+                grpc_client_settings_type,
+                quote::quote!(channel: #new_channel_slot),
+                quote::quote!(),
+            )
+        }
+    };
+
+    let client_error_enum = super::client_errors::generate_client_error_enum(struct_name, vis);
+    let shutdown_struct = super::shutdown::generate_shutdown_struct(struct_name);
+    let fn_shutdown = super::shutdown::generate_shutdown_methods(vis, &channel_source.generate_close_channels());
+
+    let builder_defaults = BuilderDefaults {
+        request_timeout,
+        ping_timeout,
+        ping_interval,
+        retries,
+        metadata: super::client_builder::read_metadata(&attributes)?,
+    };
+
+    client_fields.check_user_field_names()?;
+
+    let (builder_types, fn_new) = super::client_builder::generate_builder(struct_name, vis, &settings_type, &channel_initializer, &client_fields, &builder_defaults, with_client_options);
+
+    let fields = &client_fields.fields;
+
+
+    let fn_create_service = if extensions_telemetry{
         quote::quote!{
             fn create_service(&self, channel: tonic::transport::Channel, ctx: &my_telemetry::MyTelemetryContext) -> TGrpcService {
                 #grpc_service_name_token::with_interceptor(
@@ -100,19 +485,26 @@ pub fn generate(
         }
     };
 
-    let t_grpc_service = if with_telemetry{
+    let t_grpc_service = if extensions_telemetry{
         quote::quote!(#grpc_service_name_token<tonic::codegen::InterceptedService<tonic::transport::Channel, my_grpc_extensions::GrpcClientInterceptor>>)
     }else{
         quote::quote!(#grpc_service_name_token<tonic::transport::Channel>)
     };
 
+    let track_config_file = match &config_file_path {
+        Some(path) => super::config_file::generate_track_config_file(path),
+        None => quote::quote!(),
+    };
+
     Ok(quote::quote! {
 
         #(#use_name_spaces;)*
 
+        #track_config_file
+
         type TGrpcService = #t_grpc_service;
 
-        struct MyGrpcServiceFactory;
+        #factory_struct
 
         #[async_trait::async_trait]
         impl my_grpc_extensions::GrpcServiceFactory<TGrpcService> for MyGrpcServiceFactory {
@@ -122,39 +514,111 @@ pub fn generate(
             #struct_name::get_service_name()
         }
 
-        async fn ping(&self, mut service: TGrpcService) {
-           service.ping(()).await.unwrap();
-        }
+        #fn_ping
       }
 
-      pub struct #struct_name{
-        channel: my_grpc_extensions::GrpcChannel<TGrpcService>,
+      #channel_types
+
+      #rate_limiter_struct
+
+      #response_cache_struct
+
+      #single_flight_struct
+
+      #record_replay_struct
+
+      #fault_injector_struct
+
+      #payload_logger_struct
+
+      #metrics_struct
+
+      #observability_types
+
+      #client_error_enum
+
+      #shutdown_struct
+
+      #builder_types
+
+      #client_options_types
+
+      #(#struct_attrs)*
+      #vis struct #struct_name{
+        #channel_field,
+        #(#fields,)*
       }
 
       impl #struct_name{
-        pub fn new(get_grpc_address: std::sync::Arc<dyn my_grpc_extensions::GrpcClientSettings + Send + Sync + 'static>,) -> Self {
-            Self {
-                channel: my_grpc_extensions::GrpcChannel::new(
-                    get_grpc_address,
-                    std::sync::Arc::new(MyGrpcServiceFactory),
-                    std::time::Duration::from_secs(#timeout_sec),
-                    std::time::Duration::from_secs(#ping_timeout_sec),
-                    std::time::Duration::from_secs(#ping_interval_sec),
-                ),
-            }
-        }
+        #fn_new
+
+        #channel_methods
 
-        pub fn get_service_name() -> &'static str {
+        #vis fn get_service_name() -> &'static str {
             #settings_service_name
         }
 
+        #fn_get_fault_injector
+
+        #fn_shutdown
+
         #(#grpc_methods)*  
       }
 
-      #(#interfaces)*  
+      #(#interfaces)*
+
+      #client_api
+
+      #client_mock
+
+      #blocking_client
+
+      #fake_server
     }
     .into())
 }
 
 
 
+
+fn check_timings(
+    attributes: &ParamsList,
+    request_timeout: Duration,
+    ping_timeout: Duration,
+    ping_interval: Duration,
+) -> Result<(), syn::Error> {
+    let get_param = |name: &str, legacy_name: &str| {
+        attributes
+            .try_get_named_param(name)
+            .or_else(|| attributes.try_get_named_param(legacy_name))
+    };
+
+    if request_timeout.is_zero() {
+        if let Some(value) = get_param("request_timeout", "request_timeout_sec") {
+            return Err(value.throw_error("request_timeout must be greater than 0"));
+        }
+    }
+
+    if ping_timeout.is_zero() {
+        if let Some(value) = get_param("ping_timeout", "ping_timeout_sec") {
+            return Err(value.throw_error("ping_timeout must be greater than 0"));
+        }
+    }
+
+    // Ping which is not finished before the next one is due means a disconnect is detected too late
+    if ping_timeout >= ping_interval {
+        let message = format!(
+            "ping_timeout ({:?}) must be less than ping_interval ({:?})",
+            ping_timeout, ping_interval
+        );
+
+        return match get_param("ping_timeout", "ping_timeout_sec")
+            .or_else(|| get_param("ping_interval", "ping_interval_sec"))
+        {
+            Some(value) => Err(value.throw_error(message.as_str())),
+            None => Err(syn::Error::new_spanned(attributes.get_token_stream().clone(), message)),
+        };
+    }
+
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::{collections::HashMap, str::FromStr};
+
+use proc_macro2::Ident;
+
+use super::{fn_override::FnOverride, proto_file_reader::ProtoServiceDescription};
+
+pub fn get_blocking_client_name(struct_name: &Ident) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::from_str(format!("{}Blocking", struct_name).as_str()).unwrap()
+}
+
+// Client is created inside the runtime, so the ping loop and reconnects of GrpcChannel run on it.
+// Runtime must be a multi thread one: blocked caller of a current thread runtime would wait for itself
+pub fn generate_blocking_client(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    proto_file: &ProtoServiceDescription,
+    overrides: &HashMap<String, FnOverride>,
+    with_telemetry: bool,
+) -> proc_macro2::TokenStream {
+    let blocking_name = get_blocking_client_name(struct_name);
+
+    let ctx_param = if with_telemetry {
+        quote::quote!(ctx: &my_telemetry::MyTelemetryContext,)
+    } else {
+        quote::quote!()
+    };
+
+    let ctx_invoke = if with_telemetry {
+        quote::quote!(ctx,)
+    } else {
+        quote::quote!()
+    };
+
+    let mut methods = Vec::new();
+
+    for rpc in &proto_file.rpc {
+        let fn_name = rpc.get_client_fn_name_as_token();
+        let fn_vis = super::fn_override::get_fn_visibility(overrides, rpc.name.as_str(), vis);
+
+        let input_data_type = super::get_func_in_data_type(rpc.get_input_param().as_ref());
+        let output_data_type = super::get_func_out_data_type(rpc.get_output_param().as_ref());
+
+        methods.push(quote::quote! {
+            #fn_vis fn #fn_name(
+                &self,
+                input_data: #input_data_type,
+                #ctx_param
+            ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
+                self.block_on(self.client.#fn_name(input_data, #ctx_invoke))
+            }
+        });
+    }
+
+    quote::quote! {
+        #vis struct #blocking_name {
+            client: #struct_name,
+            handle: tokio::runtime::Handle,
+            _runtime: Option<tokio::runtime::Runtime>,
+        }
+
+        impl #blocking_name {
+            pub fn new(create_client: impl FnOnce() -> #struct_name) -> std::io::Result<Self> {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .enable_all()
+                    .build()?;
+
+                let result = Self::with_handle(runtime.handle().clone(), create_client)?;
+
+                Ok(Self {
+                    _runtime: Some(runtime),
+                    ..result
+                })
+            }
+
+            pub fn with_handle(
+                handle: tokio::runtime::Handle,
+                create_client: impl FnOnce() -> #struct_name,
+            ) -> std::io::Result<Self> {
+                if let tokio::runtime::RuntimeFlavor::CurrentThread = handle.runtime_flavor() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Blocking client needs a multi thread runtime. Handle of a current thread runtime is given",
+                    ));
+                }
+
+                let client = {
+                    let _guard = handle.enter();
+                    create_client()
+                };
+
+                Ok(Self {
+                    client,
+                    handle,
+                    _runtime: None,
+                })
+            }
+
+            fn block_on<TFuture: std::future::Future>(&self, future: TFuture) -> TFuture::Output {
+                if tokio::runtime::Handle::try_current().is_ok() {
+                    panic!(
+                        "{} is called from inside of an async context. Use the async client instead",
+                        stringify!(#blocking_name)
+                    );
+                }
+
+                self.handle.block_on(future)
+            }
+
+            pub fn get_client(&self) -> &#struct_name {
+                &self.client
+            }
+
+            pub fn shutdown(&self, drain_timeout: std::time::Duration) -> bool {
+                self.block_on(self.client.shutdown(drain_timeout))
+            }
+
+            pub fn is_shut_down(&self) -> bool {
+                self.client.is_shut_down()
+            }
+
+            #(#methods)*
+        }
+    }
+}
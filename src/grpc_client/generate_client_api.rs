@@ -0,0 +1,106 @@
+use std::{collections::HashMap, str::FromStr};
+
+use proc_macro2::Ident;
+
+use super::{fn_override::FnOverride, proto_file_reader::ProtoServiceDescription};
+
+pub fn get_client_api_name(struct_name: &Ident) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::from_str(format!("{}Api", struct_name).as_str()).unwrap()
+}
+
+// Trait with one method per rpc, so business logic can depend on Arc<dyn {ClientName}Api> instead of the client itself
+pub fn generate_client_api(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    proto_file: &ProtoServiceDescription,
+    overrides: &HashMap<String, FnOverride>,
+    with_telemetry: bool,
+) -> proc_macro2::TokenStream {
+    let api_name = get_client_api_name(struct_name);
+
+    let ctx_param = if with_telemetry {
+        quote::quote!(ctx: &my_telemetry::MyTelemetryContext,)
+    } else {
+        quote::quote!()
+    };
+
+    let ctx_invoke = if with_telemetry {
+        quote::quote!(ctx,)
+    } else {
+        quote::quote!()
+    };
+
+    let mut trait_methods = Vec::new();
+    let mut client_methods = Vec::new();
+    let mut arc_methods = Vec::new();
+
+    for rpc in &proto_file.rpc {
+        if super::fn_override::is_hidden_from_api(overrides, rpc.name.as_str(), vis) {
+            continue;
+        }
+
+        let fn_name = rpc.get_client_fn_name_as_token();
+
+        let input_data_type = super::get_func_in_data_type(rpc.get_input_param().as_ref());
+        let output_data_type = super::get_func_out_data_type(rpc.get_output_param().as_ref());
+
+        let signature = quote::quote! {
+            async fn #fn_name(
+                &self,
+                input_data: #input_data_type,
+                #ctx_param
+            ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError>
+        };
+
+        trait_methods.push(quote::quote!(#signature;));
+
+        client_methods.push(quote::quote! {
+            #signature {
+                #struct_name::#fn_name(self, input_data, #ctx_invoke).await
+            }
+        });
+
+        arc_methods.push(quote::quote! {
+            #signature {
+                (**self).#fn_name(input_data, #ctx_invoke).await
+            }
+        });
+    }
+
+    quote::quote! {
+        #[async_trait::async_trait]
+        #vis trait #api_name: Send + Sync {
+            #(#trait_methods)*
+
+            async fn shutdown(&self, drain_timeout: std::time::Duration) -> bool;
+
+            fn is_shut_down(&self) -> bool;
+        }
+
+        #[async_trait::async_trait]
+        impl #api_name for #struct_name {
+            #(#client_methods)*
+
+            async fn shutdown(&self, drain_timeout: std::time::Duration) -> bool {
+                #struct_name::shutdown(self, drain_timeout).await
+            }
+
+            fn is_shut_down(&self) -> bool {
+                #struct_name::is_shut_down(self)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl<T: #api_name + ?Sized> #api_name for std::sync::Arc<T> {
+            #(#arc_methods)*
+
+            async fn shutdown(&self, drain_timeout: std::time::Duration) -> bool {
+                (**self).shutdown(drain_timeout).await
+            }
+
+            fn is_shut_down(&self) -> bool {
+                (**self).is_shut_down()
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::{collections::HashMap, str::FromStr};
+
+use proc_macro2::Ident;
+
+use super::{fn_override::FnOverride, proto_file_reader::ProtoServiceDescription};
+
+// Mock implements {ClientName}Api. Tests queue results per method (optionally matched by input predicate)
+// and then check which inputs each method was called with.
+// Locks are recovered when poisoned, so a panic in a predicate or a missing result does not break the other methods.
+// Shutdown of the mock only marks it as shut down, so tests can check that the code under test stops the client.
+pub fn generate_client_mock(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    proto_file: &ProtoServiceDescription,
+    overrides: &HashMap<String, FnOverride>,
+    with_telemetry: bool,
+) -> proc_macro2::TokenStream {
+    let api_name = super::generate_client_api::get_client_api_name(struct_name);
+    let mock_name =
+        proc_macro2::TokenStream::from_str(format!("{}Mock", struct_name).as_str()).unwrap();
+    let mock_method_name =
+        proc_macro2::TokenStream::from_str(format!("{}MockMethod", struct_name).as_str()).unwrap();
+
+    let ctx_param = if with_telemetry {
+        quote::quote!(_ctx: &my_telemetry::MyTelemetryContext,)
+    } else {
+        quote::quote!()
+    };
+
+    let mut fields = Vec::new();
+    let mut initializers = Vec::new();
+    let mut mock_methods = Vec::new();
+    let mut api_methods = Vec::new();
+
+    for rpc in &proto_file.rpc {
+        if super::fn_override::is_hidden_from_api(overrides, rpc.name.as_str(), vis) {
+            continue;
+        }
+
+        let fn_name = rpc.get_client_fn_name_as_token();
+        let rpc_name = rpc.name.as_str();
+
+        let input_data_type = super::get_func_in_data_type(rpc.get_input_param().as_ref());
+        let output_data_type = super::get_func_out_data_type(rpc.get_output_param().as_ref());
+
+        let returns_fn_name =
+            proc_macro2::TokenStream::from_str(format!("{}_returns", fn_name).as_str()).unwrap();
+        let returns_when_fn_name =
+            proc_macro2::TokenStream::from_str(format!("{}_returns_when", fn_name).as_str())
+                .unwrap();
+        let calls_fn_name =
+            proc_macro2::TokenStream::from_str(format!("{}_calls", fn_name).as_str()).unwrap();
+
+        fields.push(quote::quote! {
+            #fn_name: std::sync::Mutex<#mock_method_name<#input_data_type, #output_data_type>>
+        });
+
+        initializers.push(quote::quote! {
+            #fn_name: std::sync::Mutex::new(#mock_method_name::new())
+        });
+
+        mock_methods.push(quote::quote! {
+            pub fn #returns_fn_name(
+                &self,
+                result: Result<#output_data_type, my_grpc_extensions::GrpcReadError>,
+            ) -> &Self {
+                self.#fn_name
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .enqueue(None, result);
+                self
+            }
+
+            pub fn #returns_when_fn_name(
+                &self,
+                predicate: impl Fn(&#input_data_type) -> bool + Send + Sync + 'static,
+                result: Result<#output_data_type, my_grpc_extensions::GrpcReadError>,
+            ) -> &Self {
+                self.#fn_name
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .enqueue(Some(Box::new(predicate)), result);
+                self
+            }
+
+            pub fn #calls_fn_name(&self) -> Vec<#input_data_type> {
+                self.#fn_name
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .calls
+                    .clone()
+            }
+        });
+
+        api_methods.push(quote::quote! {
+            async fn #fn_name(
+                &self,
+                input_data: #input_data_type,
+                #ctx_param
+            ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
+                self.#fn_name
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .call(#rpc_name, input_data)
+            }
+        });
+    }
+
+    quote::quote! {
+        #vis struct #mock_method_name<TInput: Clone + std::fmt::Debug, TOutput> {
+            results: Vec<(
+                Option<Box<dyn Fn(&TInput) -> bool + Send + Sync>>,
+                Result<TOutput, my_grpc_extensions::GrpcReadError>,
+            )>,
+            calls: Vec<TInput>,
+        }
+
+        impl<TInput: Clone + std::fmt::Debug, TOutput> #mock_method_name<TInput, TOutput> {
+            fn new() -> Self {
+                Self {
+                    results: Vec::new(),
+                    calls: Vec::new(),
+                }
+            }
+
+            fn enqueue(
+                &mut self,
+                predicate: Option<Box<dyn Fn(&TInput) -> bool + Send + Sync>>,
+                result: Result<TOutput, my_grpc_extensions::GrpcReadError>,
+            ) {
+                self.results.push((predicate, result));
+            }
+
+            fn call(
+                &mut self,
+                rpc_name: &str,
+                input_data: TInput,
+            ) -> Result<TOutput, my_grpc_extensions::GrpcReadError> {
+                let index = self.results.iter().position(|(predicate, _)| match predicate {
+                    Some(predicate) => predicate(&input_data),
+                    None => true,
+                });
+
+                let index = match index {
+                    Some(index) => index,
+                    None => panic!("No mocked result for {} with input {:?}", rpc_name, input_data),
+                };
+
+                self.calls.push(input_data);
+                self.results.remove(index).1
+            }
+        }
+
+        #vis struct #mock_name {
+            #(#fields,)*
+            is_shut_down: std::sync::atomic::AtomicBool,
+        }
+
+        impl #mock_name {
+            pub fn new() -> Self {
+                Self {
+                    #(#initializers,)*
+                    is_shut_down: std::sync::atomic::AtomicBool::new(false),
+                }
+            }
+
+            #(#mock_methods)*
+        }
+
+        impl Default for #mock_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl #api_name for #mock_name {
+            #(#api_methods)*
+
+            async fn shutdown(&self, _drain_timeout: std::time::Duration) -> bool {
+                self.is_shut_down
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+                true
+            }
+
+            fn is_shut_down(&self) -> bool {
+                self.is_shut_down.load(std::sync::atomic::Ordering::SeqCst)
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+use proc_macro2::Ident;
+
+use super::{
+    proto_file_reader::{into_snake_case, ProtoServiceDescription},
+    ParamType,
+};
+
+// Fake server implements tonic server trait of the same proto with handlers set per rpc.
+// Handlers return futures, so a slow server is simulated with tokio::time::sleep without blocking the runtime.
+// It is started on an ephemeral localhost port and its handle is GrpcClientSettings pointing to it.
+pub fn generate_fake_server(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    proto_file: &ProtoServiceDescription,
+    crate_ns: &str,
+) -> proc_macro2::TokenStream {
+    let fake_server_name =
+        proc_macro2::TokenStream::from_str(format!("{}FakeServer", struct_name).as_str()).unwrap();
+    let handle_name =
+        proc_macro2::TokenStream::from_str(format!("{}FakeServerHandle", struct_name).as_str())
+            .unwrap();
+
+    let server_name = proto_file.get_server_name();
+    let server_ns = format!("{}::{}_server", crate_ns, into_snake_case(server_name));
+    let server_trait =
+        proc_macro2::TokenStream::from_str(format!("{}::{}", server_ns, server_name).as_str())
+            .unwrap();
+    let server_type =
+        proc_macro2::TokenStream::from_str(format!("{}::{}Server", server_ns, server_name).as_str())
+            .unwrap();
+
+    let mut fields = Vec::new();
+    let mut initializers = Vec::new();
+    let mut setters = Vec::new();
+    let mut server_methods = Vec::new();
+
+    for rpc in &proto_file.rpc {
+        let fn_name = rpc.get_fn_name_as_token();
+        let rpc_name = rpc.name.as_str();
+
+        let input_param = rpc.get_input_param();
+        let output_param = rpc.get_output_param();
+
+        let handler_in_type = get_handler_data_type(input_param.as_ref());
+        let handler_out_type = get_handler_data_type(output_param.as_ref());
+
+        let setter_name =
+            proc_macro2::TokenStream::from_str(format!("on_{}", fn_name).as_str()).unwrap();
+
+        fields.push(quote::quote! {
+            #fn_name: Option<
+                Box<
+                    dyn Fn(
+                            #handler_in_type,
+                        ) -> std::pin::Pin<
+                            Box<dyn std::future::Future<Output = Result<#handler_out_type, tonic::Status>> + Send>,
+                        > + Send
+                        + Sync,
+                >,
+            >
+        });
+
+        initializers.push(quote::quote!(#fn_name: None));
+
+        setters.push(quote::quote! {
+            pub fn #setter_name<TFuture>(
+                mut self,
+                handler: impl Fn(#handler_in_type) -> TFuture + Send + Sync + 'static,
+            ) -> Self
+            where
+                TFuture: std::future::Future<Output = Result<#handler_out_type, tonic::Status>> + Send + 'static,
+            {
+                self.#fn_name = Some(Box::new(move |input_data| Box::pin(handler(input_data))));
+                self
+            }
+        });
+
+        let (request_type, read_input) = match &input_param {
+            Some(ParamType::Stream(name)) => {
+                let item_type = proc_macro2::TokenStream::from_str(name).unwrap();
+                (
+                    quote::quote!(tonic::Streaming<#item_type>),
+                    quote::quote! {
+                        let mut stream = request.into_inner();
+                        let mut input_data = Vec::new();
+                        while let Some(item) = stream.message().await? {
+                            input_data.push(item);
+                        }
+                    },
+                )
+            }
+            _ => (
+                quote::quote!(#handler_in_type),
+                quote::quote!(let input_data = request.into_inner();),
+            ),
+        };
+
+        let call_handler = quote::quote! {
+            let handler = match &self.#fn_name {
+                Some(handler) => handler,
+                None => {
+                    return Err(tonic::Status::unimplemented(format!(
+                        "Fake server has no handler for {}",
+                        #rpc_name
+                    )))
+                }
+            };
+
+            let result = handler(input_data).await?;
+        };
+
+        let server_method = match &output_param {
+            Some(ParamType::Stream(name)) => {
+                let item_type = proc_macro2::TokenStream::from_str(name).unwrap();
+                let stream_type_name = proc_macro2::TokenStream::from_str(
+                    format!("{}Stream", rpc_name).as_str(),
+                )
+                .unwrap();
+
+                quote::quote! {
+                    type #stream_type_name = std::pin::Pin<
+                        Box<dyn futures::Stream<Item = Result<#item_type, tonic::Status>> + Send + 'static>,
+                    >;
+
+                    async fn #fn_name(
+                        &self,
+                        request: tonic::Request<#request_type>,
+                    ) -> Result<tonic::Response<Self::#stream_type_name>, tonic::Status> {
+                        #read_input
+                        #call_handler
+                        Ok(tonic::Response::new(Box::pin(futures::stream::iter(
+                            result.into_iter().map(Ok),
+                        ))))
+                    }
+                }
+            }
+            _ => quote::quote! {
+                async fn #fn_name(
+                    &self,
+                    request: tonic::Request<#request_type>,
+                ) -> Result<tonic::Response<#handler_out_type>, tonic::Status> {
+                    #read_input
+                    #call_handler
+                    Ok(tonic::Response::new(result))
+                }
+            },
+        };
+
+        server_methods.push(server_method);
+    }
+
+    quote::quote! {
+        #vis struct #fake_server_name {
+            #(#fields,)*
+            pings: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        }
+
+        impl #fake_server_name {
+            pub fn new() -> Self {
+                Self {
+                    #(#initializers,)*
+                    pings: std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+                }
+            }
+
+            #(#setters)*
+
+            pub async fn start(self) -> std::io::Result<#handle_name> {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+                let url = format!("http://{}", listener.local_addr()?);
+
+                let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<()>();
+                let pings = self.pings.clone();
+
+                tokio::spawn(async move {
+                    tonic::transport::Server::builder()
+                        .add_service(#server_type::new(self))
+                        .serve_with_incoming_shutdown(
+                            tokio_stream::wrappers::TcpListenerStream::new(listener),
+                            async {
+                                let _ = shutdown_receiver.await;
+                            },
+                        )
+                        .await
+                        .unwrap();
+                });
+
+                Ok(#handle_name {
+                    url,
+                    pings,
+                    shutdown: Some(shutdown_sender),
+                })
+            }
+        }
+
+        #[tonic::async_trait]
+        impl #server_trait for #fake_server_name {
+            async fn ping(&self, _: tonic::Request<()>) -> Result<tonic::Response<()>, tonic::Status> {
+                self.pings.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Ok(tonic::Response::new(()))
+            }
+
+            #(#server_methods)*
+        }
+
+        #vis struct #handle_name {
+            url: String,
+            pings: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+            shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+        }
+
+        impl #handle_name {
+            pub fn get_url(&self) -> &str {
+                self.url.as_str()
+            }
+
+            // Pings are answered by the server itself, so they are counted to check the ping loop of the client
+            pub fn get_pings(&self) -> usize {
+                self.pings.load(std::sync::atomic::Ordering::SeqCst)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl my_grpc_extensions::GrpcClientSettings for #handle_name {
+            async fn get_grpc_url(&self, _: &'static str) -> String {
+                self.url.clone()
+            }
+        }
+
+        impl Drop for #handle_name {
+            fn drop(&mut self) {
+                if let Some(shutdown) = self.shutdown.take() {
+                    let _ = shutdown.send(());
+                }
+            }
+        }
+    }
+}
+
+fn get_handler_data_type(data_type: Option<&ParamType<'_>>) -> proc_macro2::TokenStream {
+    match data_type {
+        Some(ParamType::Single(name)) => proc_macro2::TokenStream::from_str(name).unwrap(),
+        Some(ParamType::Stream(name)) => {
+            let name = proc_macro2::TokenStream::from_str(name).unwrap();
+            quote::quote!(Vec<#name>)
+        }
+        None => quote::quote!(()),
+    }
+}
@@ -1,17 +1,64 @@
 use std::{collections::HashMap, str::FromStr};
 
-use super::{fn_override::FnOverride, proto_file_reader::ProtoServiceDescription, ParamType};
+use proc_macro2::Ident;
+
+use super::{
+    call_limits::{CallLimits, OnLimit},
+    channel_source::ChannelSource,
+    client_fields::ClientFields,
+    fn_override::{FnOverride, HedgeOverride},
+    payload_logging::PayloadLoggingSettings,
+    proto_file_reader::{into_snake_case, ProtoServiceDescription},
+    ParamType,
+};
+
+// Everything the methods of the client are generated from. Fields the methods need are added to ClientFields
+pub struct MethodsContext<'s, 'o> {
+    pub struct_name: &'s Ident,
+    pub vis: &'s syn::Visibility,
+    pub proto_file: &'s ProtoServiceDescription,
+    pub overrides: &'s HashMap<String, FnOverride<'o>>,
+    pub client_limits: &'s CallLimits,
+    pub payload_logging: &'s PayloadLoggingSettings,
+    pub channel_source: &'s ChannelSource,
+    pub with_record_replay: bool,
+    pub with_client_options: bool,
+    pub with_metrics: bool,
+    pub with_tracing: bool,
+    pub with_telemetry: bool,
+    pub extensions_telemetry: bool,
+}
 
 pub fn generate_grpc_methods(
-    proto_file: &ProtoServiceDescription,
-    retries_amount: usize,
-    overrides: &HashMap<String, FnOverride>,
-    width_telemetry: bool,
+    ctx: &MethodsContext,
+    client_fields: &mut ClientFields,
 ) -> Vec<proc_macro2::TokenStream> {
+    let MethodsContext {
+        struct_name,
+        vis,
+        proto_file,
+        overrides,
+        client_limits,
+        payload_logging,
+        channel_source,
+        with_record_replay,
+        with_client_options,
+        with_metrics,
+        with_tracing,
+        with_telemetry,
+        extensions_telemetry,
+    } = *ctx;
+
     let mut result = Vec::new();
 
+    let limiter_name = super::call_limits::get_rate_limiter_name(struct_name);
+    let cache_name = super::response_cache::get_response_cache_name(struct_name);
+    let single_flight_name = super::single_flight::get_single_flight_name(struct_name);
+
+    let mut cache_fields = Vec::new();
+
     for rpc in &proto_file.rpc {
-        let fn_name = rpc.get_fn_name_as_token();
+        let fn_name = rpc.get_client_fn_name_as_token();
 
         let input_param = rpc.get_input_param();
 
@@ -22,57 +69,392 @@ pub fn generate_grpc_methods(
         let output_data_type = get_func_out_data_type(output_param.as_ref());
 
         let request_fn_name = get_request_fn_name(input_param.as_ref());
-        let response_fn_name = get_response_fn_name(output_param.as_ref());
 
-        let retries_amount = if let Some(value) = overrides.get(&rpc.name) {
-            value.retries
-        } else {
-            retries_amount
-        };
+        let fn_override = overrides.get(&rpc.name);
 
-        let with_retries = if retries_amount > 0 {
-            let amount = proc_macro2::Literal::usize_unsuffixed(retries_amount);
-            quote::quote!(.with_retries(#amount))
-        } else {
-            quote::quote!()
+        let fn_vis = super::fn_override::get_fn_visibility(overrides, rpc.name.as_str(), vis);
+
+        // Client-wide retries can be changed by the builder, so they are read at runtime
+        let retries = match fn_override.and_then(|value| value.retries) {
+            Some(0) => Retries::Off,
+            Some(value) => Retries::Fixed(proc_macro2::Literal::usize_unsuffixed(value)),
+            None if with_client_options => {
+                Retries::Runtime(quote::quote!(self.live_options.get_retries()))
+            }
+            None => Retries::Runtime(quote::quote!(self.retries)),
         };
 
-        let ctx_param = if width_telemetry {
+        let ctx_param = if extensions_telemetry {
             quote::quote!(ctx: &my_telemetry::MyTelemetryContext,)
         } else {
             quote::quote!()
         };
 
-        let get_channel = if width_telemetry {
-            quote::quote!(self.channel.get_channel(ctx).await.unwrap())
+        let get_channel = if extensions_telemetry {
+            quote::quote!(get_channel(ctx).await.unwrap())
         } else {
-            quote::quote!(self.channel.get_channel().await.unwrap())
+            quote::quote!(get_channel().await.unwrap())
         };
 
-        let item = quote::quote! {
-            pub async fn #fn_name(
-                &self,
-                input_data: #input_data_type,
-                #ctx_param
-            ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
-                let channel = #get_channel;
+        let get_channel = channel_source.generate_get_channel(struct_name, get_channel);
+
+        // Limits of the method are applied on top of the client-wide ones
+        let method_limits = fn_override.map(|value| &value.limits);
+
+        let on_limit = method_limits
+            .and_then(|limits| limits.on_limit)
+            .or(client_limits.on_limit)
+            .unwrap_or(OnLimit::Wait);
+
+        let acquire_client_limits =
+            client_limits.generate_acquire(struct_name, "", rpc.name.as_str(), on_limit);
+
+        let acquire_method_limits = match method_limits {
+            Some(limits) if !limits.is_empty() => {
+                let field_prefix = format!("{}_", into_snake_case(rpc.name.as_str()));
+                limits.add_fields(field_prefix.as_str(), &limiter_name, client_fields);
+                limits.generate_acquire(struct_name, field_prefix.as_str(), rpc.name.as_str(), on_limit)
+            }
+            _ => quote::quote!(),
+        };
+
+        let acquire_limits = quote::quote!(#acquire_client_limits #acquire_method_limits);
+
+        let call_body = if let Some(hedge) = fn_override.and_then(|value| value.hedge.as_ref()) {
+            let (create_executor, executor) = super::executor_call::generate_create_executor(
+                struct_name,
+                quote::quote!(is_hedge),
+                with_metrics,
+                with_tracing,
+            );
+            let response_fn_name = get_response_fn_name(output_param.as_ref(), &executor);
+
+            let send_request =
+                generate_send_request(&retries, &request_fn_name, &response_fn_name);
+            let send_hedge =
+                generate_send_request(&Retries::Off, &request_fn_name, &response_fn_name);
+
+            let hedged_body = generate_hedged_body(
+                hedge,
+                &get_channel,
+                &create_executor,
+                &send_request,
+                &send_hedge,
+            );
+
+            quote::quote! {
+                #acquire_limits
+                #hedged_body
+            }
+        } else {
+            let (create_executor, executor) = super::executor_call::generate_create_executor(
+                struct_name,
+                quote::quote!(false),
+                with_metrics,
+                with_tracing,
+            );
+            let response_fn_name = get_response_fn_name(output_param.as_ref(), &executor);
+            let send_request = generate_send_request(&retries, &request_fn_name, &response_fn_name);
+
+            quote::quote! {
+                #acquire_limits
+
+                #get_channel
+                #create_executor
 
-                let result = channel
-                    .#request_fn_name(input_data)
-                    #with_retries
-                    .#response_fn_name;
+                let result = #send_request;
 
                 Ok(result)
+            }
+        };
+
+        let call_body = if fn_override.map(|value| value.single_flight).unwrap_or(false) {
+            let single_flight_field = get_fn_name_with_suffix(rpc.name.as_str(), "single_flight");
+
+            client_fields.add(
+                &single_flight_field,
+                quote::quote!(#single_flight_name<#output_data_type>),
+                quote::quote!(#single_flight_name::new()),
+            );
+
+            super::single_flight::wrap_with_single_flight(
+                struct_name,
+                &single_flight_field,
+                call_body,
+                &output_data_type,
+            )
+        } else {
+            call_body
+        };
+
+        let fn_body = if let Some(cache) = fn_override.and_then(|value| value.cache.as_ref()) {
+            let cache_field = get_fn_name_with_suffix(rpc.name.as_str(), "cache");
+            let invalidate_fn_name =
+                proc_macro2::TokenStream::from_str(format!("invalidate_{}", fn_name).as_str())
+                    .unwrap();
+
+            client_fields.add(
+                &cache_field,
+                cache.get_field_type(&cache_name, &output_data_type),
+                cache.get_field_initializer(&cache_name),
+            );
+
+            result.push(quote::quote! {
+                #fn_vis fn #invalidate_fn_name(&self) {
+                    self.#cache_field.clear();
+                }
+            });
+
+            let telemetry_event_name = format!("{}::{} cache", struct_name, rpc.name);
+
+            let fn_body = super::response_cache::wrap_with_cache(
+                &cache_field,
+                telemetry_event_name.as_str(),
+                extensions_telemetry,
+                call_body,
+                &output_data_type,
+            );
+
+            cache_fields.push(cache_field);
+
+            fn_body
+        } else {
+            call_body
+        };
+
+        let fn_body = if with_metrics {
+            super::metrics::wrap_with_metrics(
+                struct_name,
+                rpc.name.as_str(),
+                fn_body,
+                &output_data_type,
+            )
+        } else {
+            fn_body
+        };
+
+        let log_payloads = match fn_override.and_then(|value| value.log_payloads) {
+            Some(value) => value,
+            None => payload_logging.verbosity,
+        };
 
+        let fn_body = super::payload_logging::wrap_with_payload_logging(
+            struct_name,
+            rpc.name.as_str(),
+            log_payloads,
+            fn_body,
+            &output_data_type,
+        );
+
+        let fn_body = if with_tracing {
+            super::trace_spans::wrap_with_span(
+                struct_name,
+                rpc.name.as_str(),
+                fn_body,
+                &output_data_type,
+            )
+        } else {
+            fn_body
+        };
+
+        let fn_body = if with_record_replay {
+            super::record_replay::wrap_with_record_replay(
+                struct_name,
+                rpc.name.as_str(),
+                input_param.as_ref(),
+                output_param.as_ref(),
+                fn_body,
+                &output_data_type,
+            )
+        } else {
+            fn_body
+        };
+
+        let fn_body = super::shutdown::wrap_with_shutdown_guard(rpc.name.as_str(), fn_body);
+
+        // Extensions take ctx, but methods of the client do not. Plain method goes with a new context
+        // and {fn_name}_with_ctx is there for the callers which have one
+        let item = if extensions_telemetry && !with_telemetry {
+            let fn_name_with_ctx =
+                proc_macro2::TokenStream::from_str(format!("{}_with_ctx", fn_name).as_str())
+                    .unwrap();
+
+            quote::quote! {
+                #fn_vis async fn #fn_name(
+                    &self,
+                    input_data: #input_data_type,
+                ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
+                    self.#fn_name_with_ctx(input_data, &my_telemetry::MyTelemetryContext::new())
+                        .await
+                }
+
+                #fn_vis async fn #fn_name_with_ctx(
+                    &self,
+                    input_data: #input_data_type,
+                    #ctx_param
+                ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
+                    #fn_body
+                }
+            }
+        } else {
+            quote::quote! {
+                #fn_vis async fn #fn_name(
+                    &self,
+                    input_data: #input_data_type,
+                    #ctx_param
+                ) -> Result<#output_data_type, my_grpc_extensions::GrpcReadError> {
+                    #fn_body
+                }
             }
         };
 
         result.push(item);
     }
 
+    if with_record_replay {
+        let record_replay_name = super::record_replay::get_record_replay_name(struct_name);
+
+        client_fields.add(
+            &quote::quote!(record_replay),
+            quote::quote!(#record_replay_name),
+            quote::quote!(#record_replay_name::new()),
+        );
+
+        result.push(super::record_replay::generate_record_replay_client_methods(vis));
+    }
+
+    if !cache_fields.is_empty() {
+        result.push(quote::quote! {
+            #vis fn clear_cache(&self) {
+                #(self.#cache_fields.clear();)*
+            }
+        });
+    }
+
     result
 }
 
+fn get_fn_name_with_suffix(rpc_name: &str, suffix: &str) -> proc_macro2::TokenStream {
+    proc_macro2::TokenStream::from_str(
+        format!("{}_{}", into_snake_case(rpc_name), suffix).as_str(),
+    )
+    .unwrap()
+}
+
+// Starts one more identical call each time the hedge timer fires and returns the first success.
+// Limits are acquired once for the whole call. Only the first attempt is retried, hedges are extra attempts themselves.
+// Timer is restarted only when a hedge is started, so failed attempts do not delay the next hedge.
+// Calls which are still in flight are cancelled by being dropped together with the stream.
+fn generate_hedged_body(
+    hedge: &HedgeOverride,
+    get_channel: &proc_macro2::TokenStream,
+    create_executor: &proc_macro2::TokenStream,
+    send_request: &proc_macro2::TokenStream,
+    send_hedge: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let after = super::duration_param::duration_to_tokens(hedge.after);
+    let max_extra = proc_macro2::Literal::usize_unsuffixed(hedge.max_extra);
+
+    quote::quote! {
+        use futures::{FutureExt, StreamExt};
+
+        let start_attempt = |is_hedge: bool| {
+            let input_data = input_data.clone();
+
+            async move {
+                #get_channel
+                #create_executor
+
+                let result = if is_hedge {
+                    #send_hedge
+                } else {
+                    #send_request
+                };
+
+                Ok::<_, my_grpc_extensions::GrpcReadError>(result)
+            }
+            .boxed()
+        };
+
+        let hedge_timer = tokio::time::sleep(#after);
+        tokio::pin!(hedge_timer);
+
+        let mut in_flight = futures::stream::FuturesUnordered::new();
+        in_flight.push(start_attempt(false));
+
+        let mut extra_started = 0;
+
+        loop {
+            tokio::select! {
+                Some(result) = in_flight.next() => {
+                    match result {
+                        Ok(result) => return Ok(result),
+                        Err(err) => {
+                            if in_flight.is_empty() {
+                                if extra_started >= #max_extra {
+                                    return Err(err);
+                                }
+
+                                in_flight.push(start_attempt(true));
+                                extra_started += 1;
+                                hedge_timer.as_mut().reset(tokio::time::Instant::now() + #after);
+                            }
+                        }
+                    }
+                }
+                _ = &mut hedge_timer, if extra_started < #max_extra => {
+                    in_flight.push(start_attempt(true));
+                    extra_started += 1;
+                    hedge_timer.as_mut().reset(tokio::time::Instant::now() + #after);
+                }
+            }
+        }
+    }
+}
+
+enum Retries {
+    Off,
+    Fixed(proc_macro2::Literal),
+    Runtime(proc_macro2::TokenStream),
+}
+
+// with_retries is not called for 0 retries, so 0 set by the builder works the same way as 0 in the attributes
+fn generate_send_request(
+    retries: &Retries,
+    request_fn_name: &proc_macro2::TokenStream,
+    response_fn_name: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match retries {
+        Retries::Off => quote::quote! {
+            channel
+                .#request_fn_name(input_data)
+                .#response_fn_name
+        },
+        Retries::Fixed(amount) => quote::quote! {
+            channel
+                .#request_fn_name(input_data)
+                .with_retries(#amount)
+                .#response_fn_name
+        },
+        Retries::Runtime(amount) => quote::quote! {
+            {
+                let retries = #amount;
+
+                if retries > 0 {
+                    channel
+                        .#request_fn_name(input_data)
+                        .with_retries(retries)
+                        .#response_fn_name
+                } else {
+                    channel
+                        .#request_fn_name(input_data)
+                        .#response_fn_name
+                }
+            }
+        },
+    }
+}
+
 fn get_request_fn_name(input_param: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
     match input_param {
         Some(input_param) => {
@@ -88,24 +470,27 @@ fn get_request_fn_name(input_param: Option<&super::ParamType<'_>>) -> proc_macro
     }
 }
 
-fn get_response_fn_name(input_param: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
+fn get_response_fn_name(
+    input_param: Option<&super::ParamType<'_>>,
+    executor: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
     match input_param {
         Some(input_param) => {
             if input_param.is_stream() {
-                quote::quote! {get_streamed_response(self).await?
+                quote::quote! {get_streamed_response(#executor).await?
                 .as_vec()
                 .await?}
             } else {
-                quote::quote! {get_response(self).await?}
+                quote::quote! {get_response(#executor).await?}
             }
         }
         None => {
-            quote::quote! {get_response(self).await?}
+            quote::quote! {get_response(#executor).await?}
         }
     }
 }
 
-fn get_func_in_data_type(data_type: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
+pub fn get_func_in_data_type(data_type: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
     match data_type {
         Some(input_param) => match input_param {
             ParamType::Single(name) => proc_macro2::TokenStream::from_str(name).unwrap(),
@@ -120,7 +505,7 @@ fn get_func_in_data_type(data_type: Option<&super::ParamType<'_>>) -> proc_macro
     }
 }
 
-fn get_func_out_data_type(data_type: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
+pub fn get_func_out_data_type(data_type: Option<&super::ParamType<'_>>) -> proc_macro2::TokenStream {
     match data_type {
         Some(input_param) => match input_param {
             ParamType::Single(name) => proc_macro2::TokenStream::from_str(name).unwrap(),
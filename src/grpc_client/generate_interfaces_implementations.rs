@@ -2,15 +2,31 @@ use std::str::FromStr;
 
 use proc_macro2::Ident;
 
-use super::{proto_file_reader::ProtoServiceDescription, ParamType};
+use super::{
+    executor_call::ExecutorFeatures, proto_file_reader::ProtoServiceDescription, ParamType,
+};
 
 pub fn generate_interfaces_implementations(
     struct_name: &Ident,
     proto_file: &ProtoServiceDescription,
+    with_fault_injection: bool,
+    features: ExecutorFeatures,
 ) -> Vec<proc_macro2::TokenStream> {
     let mut result = Vec::new();
 
+    let (impl_generics, executor_type) = super::executor_call::get_executor_type(
+        struct_name,
+        features.with_metrics,
+        features.with_tracing,
+    );
+
     for rpc in &proto_file.rpc {
+        let inject_fault = if with_fault_injection {
+            super::fault_injection::generate_inject_fault(rpc.name.as_str())
+        } else {
+            quote::quote!()
+        };
+
         if let Some(input_param_type) = &rpc.get_input_param() {
             if let Some(output_param_type) = &rpc.get_output_param() {
                 let input_param_type_token = get_name_fn_param_type_token(&input_param_type);
@@ -26,21 +42,30 @@ pub fn generate_interfaces_implementations(
 
                 let input_param_invoke = input_param_type.get_input_param_invoke_token();
 
+                let service_call = super::executor_call::generate_executor_call(
+                    struct_name,
+                    rpc.name.as_str(),
+                    &fn_name,
+                    input_param_invoke,
+                    features,
+                );
+
                 let quote = quote::quote! {
                     #[async_trait::async_trait]
-                    impl
+                    impl #impl_generics
                         #interface_name<
                             TGrpcService,
                             #input_param_name_token,
                             #output_param_name_token,
-                        > for #struct_name
+                        > for #executor_type
                     {
                         async fn execute(
                             &self,
                             mut service: TGrpcService,
                             input_data: #input_param_type_token,
                         ) -> Result<#output_param_type_token, tonic::Status> {
-                            let result = service.#fn_name(#input_param_invoke).await?;
+                            #inject_fault
+                            #service_call
                             Ok(result.into_inner())
                         }
                     }
@@ -60,21 +85,30 @@ pub fn generate_interfaces_implementations(
 
                 let input_param_invoke = input_param_type.get_input_param_invoke_token();
 
+                let service_call = super::executor_call::generate_executor_call(
+                    struct_name,
+                    rpc.name.as_str(),
+                    &fn_name,
+                    input_param_invoke,
+                    features,
+                );
+
                 let quote = quote::quote! {
                     #[async_trait::async_trait]
-                    impl
+                    impl #impl_generics
                         #interface_name<
                             TGrpcService,
                             #input_param_name_token,
                             #output_param_type_token,
-                        > for #struct_name
+                        > for #executor_type
                     {
                         async fn execute(
                             &self,
                             mut service: TGrpcService,
                             input_data: #input_param_type_token,
                         ) -> Result<#output_param_type_token, tonic::Status> {
-                            let result = service.#fn_name(#input_param_invoke).await?;
+                            #inject_fault
+                            #service_call
                             Ok(result.into_inner())
                         }
                     }
@@ -94,21 +128,30 @@ pub fn generate_interfaces_implementations(
 
                 let fn_name = rpc.get_fn_name_as_token();
 
+                let service_call = super::executor_call::generate_executor_call(
+                    struct_name,
+                    rpc.name.as_str(),
+                    &fn_name,
+                    quote::quote!(()),
+                    features,
+                );
+
                 let quote = quote::quote! {
                     #[async_trait::async_trait]
-                    impl
+                    impl #impl_generics
                         #interface_name<
                             TGrpcService,
                             #input_param_type_token,
                             #output_param_name_token,
-                        > for #struct_name
+                        > for #executor_type
                     {
                         async fn execute(
                             &self,
                             mut service: TGrpcService,
                             input_data: #input_param_type_token,
                         ) -> Result<#output_param_type_token, tonic::Status> {
-                            let result = service.#fn_name(()).await?;
+                            #inject_fault
+                            #service_call
                             Ok(result.into_inner())
                         }
                     }
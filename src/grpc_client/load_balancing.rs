@@ -0,0 +1,398 @@
+use std::{str::FromStr, time::Duration};
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+pub enum Balancer {
+    RoundRobin,
+    LeastOutstanding,
+    PowerOfTwo,
+}
+
+pub struct LoadBalancing {
+    pub balancer: Balancer,
+    pub endpoints_refresh: Duration,
+}
+
+impl LoadBalancing {
+    pub fn new(attributes: &ParamsList) -> Result<Option<Self>, syn::Error> {
+        let value = attributes.try_get_named_param("load_balancing");
+        let endpoints_refresh = attributes.try_get_named_param("endpoints_refresh");
+
+        if value.is_none() {
+            if let Some(endpoints_refresh) = endpoints_refresh {
+                return Err(endpoints_refresh
+                    .throw_error("endpoints_refresh is used only with load_balancing"));
+            }
+
+            return Ok(None);
+        }
+
+        let value = value.unwrap();
+
+        let balancer = match value.unwrap_as_string_value()?.as_str() {
+            "round_robin" => Balancer::RoundRobin,
+            "least_outstanding" => Balancer::LeastOutstanding,
+            "power_of_two" => Balancer::PowerOfTwo,
+            _ => return Err(value.throw_error(
+                "load_balancing must be \"round_robin\", \"least_outstanding\" or \"power_of_two\"",
+            )),
+        };
+
+        let endpoints_refresh = match endpoints_refresh {
+            Some(value) => match super::duration_param::parse_duration(
+                value.unwrap_as_string_value()?.as_str(),
+            ) {
+                Ok(duration) => duration,
+                Err(err) => {
+                    return Err(
+                        value.throw_error(format!("Invalid endpoints_refresh. {}", err).as_str())
+                    )
+                }
+            },
+            None => super::attributes_schema::DEFAULT_ENDPOINTS_REFRESH,
+        };
+
+        Ok(Some(Self {
+            balancer,
+            endpoints_refresh,
+        }))
+    }
+
+    fn generate_pick(&self) -> TokenStream {
+        match self.balancer {
+            Balancer::RoundRobin => quote::quote! {
+                let index = self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                candidates[index % candidates.len()]
+            },
+            Balancer::LeastOutstanding => quote::quote! {
+                let start = self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let len = candidates.len();
+                (0..len)
+                    .map(|index| candidates[(start + index) % len])
+                    .min_by_key(|endpoint| endpoint.get_outstanding())
+                    .unwrap()
+            },
+            Balancer::PowerOfTwo => quote::quote! {
+                let len = candidates.len();
+
+                if len == 1 {
+                    return candidates[0];
+                }
+
+                let get_random = || {
+                    use std::hash::{BuildHasher, Hasher};
+                    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+                    hasher.write_usize(self.next.fetch_add(1, std::sync::atomic::Ordering::Relaxed));
+                    hasher.finish() as usize
+                };
+
+                let first = get_random() % len;
+                let mut second = get_random() % (len - 1);
+                if second >= first {
+                    second += 1;
+                }
+
+                if candidates[first].get_outstanding() <= candidates[second].get_outstanding() {
+                    candidates[first]
+                } else {
+                    candidates[second]
+                }
+            },
+        }
+    }
+}
+
+pub fn get_endpoints_settings_trait_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}EndpointsSettings", struct_name).as_str()).unwrap()
+}
+
+pub fn get_endpoints_pool_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Endpoints", struct_name).as_str()).unwrap()
+}
+
+pub fn generate_load_balancing(
+    struct_name: &Ident,
+    vis: &syn::Visibility,
+    load_balancing: &LoadBalancing,
+    observe_connects: bool,
+    extensions_telemetry: bool,
+) -> TokenStream {
+    let timings_name = super::client_builder::get_timings_name(struct_name);
+    let settings_trait_name = get_endpoints_settings_trait_name(struct_name);
+    let pool_name = get_endpoints_pool_name(struct_name);
+    let health_name = super::endpoint_health::get_endpoint_health_name(struct_name);
+    let shutdown_name = super::shutdown::get_shutdown_name(struct_name);
+    let error_name = super::client_errors::get_client_error_name(struct_name);
+    let channel_slot_name = super::shutdown::get_channel_slot_name(struct_name);
+
+    let endpoint_name = TokenStream::from_str(format!("{}Endpoint", struct_name).as_str()).unwrap();
+    let endpoint_settings_name =
+        TokenStream::from_str(format!("{}EndpointSettings", struct_name).as_str()).unwrap();
+    let outstanding_guard_name =
+        TokenStream::from_str(format!("{}OutstandingGuard", struct_name).as_str()).unwrap();
+
+    let pick = load_balancing.generate_pick();
+    let endpoints_refresh =
+        super::duration_param::duration_to_tokens(load_balancing.endpoints_refresh);
+
+    let get_channel = if extensions_telemetry {
+        quote::quote!(get_channel(&my_telemetry::MyTelemetryContext::new()))
+    } else {
+        quote::quote!(get_channel())
+    };
+
+    let endpoint_settings = super::observed_settings::wrap_settings(
+        struct_name,
+        quote::quote!(std::sync::Arc::new(#endpoint_settings_name { url: url.clone() })),
+        observe_connects,
+    );
+
+    let new_channel_slot = super::shutdown::generate_new_channel_slot(
+        struct_name,
+        &endpoint_settings,
+        &[
+            (quote::quote!(health), quote::quote!(health)),
+            (quote::quote!(shutdown), quote::quote!(self.shutdown)),
+        ],
+        &quote::quote!(self.timings),
+    );
+
+    let new_health =
+        super::endpoint_health::generate_new_health(struct_name, &quote::quote!(self.timings));
+
+    quote::quote! {
+        #[async_trait::async_trait]
+        #vis trait #settings_trait_name {
+            async fn get_grpc_urls(&self, name: &'static str) -> Vec<String>;
+        }
+
+        struct #endpoint_settings_name {
+            url: String,
+        }
+
+        #[async_trait::async_trait]
+        impl my_grpc_extensions::GrpcClientSettings for #endpoint_settings_name {
+            async fn get_grpc_url(&self, _: &'static str) -> String {
+                self.url.clone()
+            }
+        }
+
+        struct #outstanding_guard_name<'s>(&'s std::sync::atomic::AtomicUsize);
+
+        impl<'s> Drop for #outstanding_guard_name<'s> {
+            fn drop(&mut self) {
+                self.0.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+
+        struct #endpoint_name {
+            url: String,
+            channel: #channel_slot_name,
+            health: std::sync::Arc<#health_name>,
+            outstanding: std::sync::atomic::AtomicUsize,
+            last_probe: std::sync::Mutex<Option<tokio::time::Instant>>,
+        }
+
+        impl #endpoint_name {
+            fn start_request(&self) -> #outstanding_guard_name<'_> {
+                self.outstanding.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                #outstanding_guard_name(&self.outstanding)
+            }
+
+            fn get_outstanding(&self) -> usize {
+                self.outstanding.load(std::sync::atomic::Ordering::SeqCst)
+            }
+
+            // Channel of the endpoint is dropped once its ping fails and calls do not go to an unhealthy endpoint,
+            // so it is connected again in the background. Ping loop of the new channel brings the health back
+            fn probe_if_unhealthy(self: &std::sync::Arc<Self>, probe_interval: std::time::Duration) {
+                if self.health.is_healthy() {
+                    return;
+                }
+
+                {
+                    let mut last_probe = self.last_probe.lock().unwrap_or_else(|err| err.into_inner());
+
+                    if let Some(last_probe) = *last_probe {
+                        if last_probe.elapsed() < probe_interval {
+                            return;
+                        }
+                    }
+
+                    *last_probe = Some(tokio::time::Instant::now());
+                }
+
+                let channel = match self.channel.get() {
+                    Some(channel) => channel,
+                    None => return,
+                };
+
+                tokio::spawn(async move {
+                    let _ = channel.#get_channel.await;
+                });
+            }
+        }
+
+        // Endpoints are resolved again after endpoints_refresh or once none of them is healthy.
+        // Endpoints which keep their url keep their channel and health. Refresh goes by the tokio clock,
+        // so paused time of tests applies to it
+        struct #pool_name {
+            settings: std::sync::Arc<dyn #settings_trait_name + Send + Sync + 'static>,
+            timings: #timings_name,
+            shutdown: std::sync::Arc<#shutdown_name>,
+            endpoints: std::sync::RwLock<Vec<std::sync::Arc<#endpoint_name>>>,
+            resolved_at: std::sync::Mutex<Option<tokio::time::Instant>>,
+            resolving: tokio::sync::Mutex<()>,
+            next: std::sync::atomic::AtomicUsize,
+        }
+
+        impl #pool_name {
+            fn new(
+                settings: std::sync::Arc<dyn #settings_trait_name + Send + Sync + 'static>,
+                timings: #timings_name,
+                shutdown: std::sync::Arc<#shutdown_name>,
+            ) -> Self {
+                Self {
+                    settings,
+                    timings,
+                    shutdown,
+                    endpoints: std::sync::RwLock::new(Vec::new()),
+                    resolved_at: std::sync::Mutex::new(None),
+                    resolving: tokio::sync::Mutex::new(()),
+                    next: std::sync::atomic::AtomicUsize::new(0),
+                }
+            }
+
+            fn get_current(&self) -> Vec<std::sync::Arc<#endpoint_name>> {
+                self.endpoints
+                    .read()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .clone()
+            }
+
+            // Settings are not asked more often than once per ping interval even if no endpoint is healthy
+            fn is_resolve_due(&self) -> bool {
+                let resolved_at = *self.resolved_at.lock().unwrap_or_else(|err| err.into_inner());
+
+                let resolved_at = match resolved_at {
+                    Some(resolved_at) => resolved_at,
+                    None => return true,
+                };
+
+                if resolved_at.elapsed() >= #endpoints_refresh {
+                    return true;
+                }
+
+                resolved_at.elapsed() >= self.timings.ping_interval
+                    && !self
+                        .get_current()
+                        .iter()
+                        .any(|endpoint| endpoint.health.is_healthy())
+            }
+
+            async fn resolve(&self) {
+                let _resolving = self.resolving.lock().await;
+
+                if !self.is_resolve_due() || self.shutdown.is_shut_down() {
+                    return;
+                }
+
+                let service_name = #struct_name::get_service_name();
+                let urls = self.settings.get_grpc_urls(service_name).await;
+
+                let current = self.get_current();
+
+                // Empty list is treated as a settings glitch once endpoints are known, so they are kept.
+                // Without known endpoints calls fail until the list is asked again after ping_interval
+                if urls.is_empty() {
+                    *self.resolved_at.lock().unwrap_or_else(|err| err.into_inner()) =
+                        Some(tokio::time::Instant::now());
+                    return;
+                }
+
+                let endpoints: Vec<std::sync::Arc<#endpoint_name>> = urls
+                    .into_iter()
+                    .map(|url| {
+                        if let Some(endpoint) = current.iter().find(|endpoint| endpoint.url == url) {
+                            return endpoint.clone();
+                        }
+
+                        let health = #new_health;
+
+                        std::sync::Arc::new(#endpoint_name {
+                            url: url.clone(),
+                            channel: #new_channel_slot,
+                            health,
+                            outstanding: std::sync::atomic::AtomicUsize::new(0),
+                            last_probe: std::sync::Mutex::new(None),
+                        })
+                    })
+                    .collect();
+
+                *self.endpoints.write().unwrap_or_else(|err| err.into_inner()) = endpoints;
+                *self.resolved_at.lock().unwrap_or_else(|err| err.into_inner()) =
+                    Some(tokio::time::Instant::now());
+            }
+
+            fn pick<'e>(&self, candidates: &[&'e std::sync::Arc<#endpoint_name>]) -> &'e std::sync::Arc<#endpoint_name> {
+                #pick
+            }
+
+            // Returns ShutDown once the client is shut down and its endpoints are dropped
+            // and NoEndpoints while settings return no urls
+            async fn get_next(&self) -> Result<std::sync::Arc<#endpoint_name>, #error_name> {
+                if self.shutdown.is_shut_down() {
+                    return Err(#error_name::ShutDown);
+                }
+
+                if self.is_resolve_due() {
+                    self.resolve().await;
+                }
+
+                let endpoints = self.get_current();
+
+                if endpoints.is_empty() {
+                    if self.shutdown.is_shut_down() {
+                        return Err(#error_name::ShutDown);
+                    }
+
+                    return Err(#error_name::NoEndpoints);
+                }
+
+                for endpoint in &endpoints {
+                    endpoint.probe_if_unhealthy(self.timings.ping_interval);
+                }
+
+                let healthy: Vec<&std::sync::Arc<#endpoint_name>> = endpoints
+                    .iter()
+                    .filter(|endpoint| endpoint.health.is_healthy())
+                    .collect();
+
+                let candidates = if healthy.is_empty() {
+                    endpoints.iter().collect()
+                } else {
+                    healthy
+                };
+
+                Ok(self.pick(&candidates).clone())
+            }
+
+            fn reconnect(&self) {
+                for endpoint in self.get_current() {
+                    endpoint.channel.reconnect();
+                }
+            }
+
+            fn close(&self) {
+                let endpoints = std::mem::take(
+                    &mut *self.endpoints.write().unwrap_or_else(|err| err.into_inner()),
+                );
+
+                for endpoint in endpoints {
+                    endpoint.channel.close();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use types_reader::ParamsList;
+
+use super::{fn_override::FnOverride, proto_file_reader::ProtoServiceDescription};
+
+// Methods which the macro generates on the client, the blocking client and the Api trait
+const RESERVED_FN_NAMES: &[&str] = &[
+    "new",
+    "builder",
+    "get_service_name",
+    "get_active_service_name",
+    "get_fault_injector",
+    "reconnect_channels",
+    "get_client",
+    "with_handle",
+    "clear_cache",
+    "start_recording",
+    "start_replay",
+    "stop_record_replay",
+    "get_unrecorded_requests",
+    "shutdown",
+    "is_shut_down",
+];
+
+fn read_method_list(
+    attributes: &ParamsList,
+    param_name: &str,
+    proto_file: &ProtoServiceDescription,
+) -> Result<Option<Vec<String>>, syn::Error> {
+    let value = match attributes.try_get_named_param(param_name) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let mut result = Vec::new();
+
+    for item in value.unwrap_as_vec()? {
+        let method_name = item.unwrap_as_string_value()?.to_string();
+
+        if !proto_file.has_method(method_name.as_str()) {
+            return Err(item.throw_error(
+                format!(
+                    "Method {} is not found in proto file for service {}",
+                    method_name, proto_file.service_name
+                )
+                .as_str(),
+            ));
+        }
+
+        result.push(method_name);
+    }
+
+    Ok(Some(result))
+}
+
+// Error is shown on the override of the method if there is one, otherwise on the whole attribute
+fn get_method_error(
+    attributes: &ParamsList,
+    overrides: &HashMap<String, FnOverride>,
+    rpc_name: &str,
+    message: String,
+) -> syn::Error {
+    match overrides.get(rpc_name) {
+        Some(fn_override) => syn::Error::new_spanned(fn_override.token_stream.clone(), message),
+        None => syn::Error::new_spanned(attributes.get_token_stream().clone(), message),
+    }
+}
+
+// Methods left in the generated client after include/exclude lists and renames from overrides are applied
+pub fn select_methods(
+    attributes: &ParamsList,
+    proto_file: &ProtoServiceDescription,
+    overrides: &HashMap<String, FnOverride>,
+) -> Result<ProtoServiceDescription, syn::Error> {
+    let include = read_method_list(attributes, "include", proto_file)?;
+    let exclude = read_method_list(attributes, "exclude", proto_file)?;
+
+    if include.is_some() && exclude.is_some() {
+        return Err(attributes
+            .get_named_param("exclude")?
+            .throw_error("include and exclude can not be used together"));
+    }
+
+    let mut result = proto_file.clone();
+
+    if let Some(include) = &include {
+        result.rpc.retain(|rpc| include.contains(&rpc.name));
+    }
+
+    if let Some(exclude) = &exclude {
+        result.rpc.retain(|rpc| !exclude.contains(&rpc.name));
+    }
+
+    for (fn_name, fn_override) in overrides {
+        if proto_file.has_method(fn_name) && !result.has_method(fn_name) {
+            return Err(syn::Error::new_spanned(
+                fn_override.token_stream.clone(),
+                format!(
+                    "Method {} is excluded from the client and can not be overridden",
+                    fn_name
+                ),
+            ));
+        }
+    }
+
+    for rpc in &mut result.rpc {
+        if let Some(fn_override) = overrides.get(&rpc.name) {
+            rpc.rename_to = fn_override.rename_to.clone();
+        }
+    }
+
+    // Functions generated per method like invalidate_{fn_name} are reserved whatever the options are,
+    // so enabling cache or telemetry does not break the names of the other methods
+    let mut generated_fn_names = HashMap::new();
+
+    for rpc in &result.rpc {
+        let fn_name = rpc.get_client_fn_name_as_token().to_string();
+        generated_fn_names.insert(format!("invalidate_{}", fn_name), rpc.name.as_str());
+        generated_fn_names.insert(format!("{}_with_ctx", fn_name), rpc.name.as_str());
+    }
+
+    let mut fn_names = HashMap::new();
+
+    for rpc in &result.rpc {
+        let fn_name = rpc.get_client_fn_name_as_token().to_string();
+
+        if let Some(other_rpc_name) = generated_fn_names.get(&fn_name) {
+            let message = format!(
+                "Method {} generates function {} which is generated by the macro for method {}. Use rename_to to give it another name",
+                rpc.name, fn_name, other_rpc_name
+            );
+
+            return Err(get_method_error(
+                attributes,
+                overrides,
+                rpc.name.as_str(),
+                message,
+            ));
+        }
+
+        if RESERVED_FN_NAMES.contains(&fn_name.as_str()) {
+            let message = format!(
+                "Method {} generates function {} which is generated by the macro. Use rename_to to give it another name",
+                rpc.name, fn_name
+            );
+
+            return Err(get_method_error(
+                attributes,
+                overrides,
+                rpc.name.as_str(),
+                message,
+            ));
+        }
+
+        if let Some(other_rpc_name) = fn_names.insert(fn_name.clone(), rpc.name.as_str()) {
+            let message = format!(
+                "Methods {} and {} both generate function {}. Use rename_to to give them different names",
+                other_rpc_name, rpc.name, fn_name
+            );
+
+            return Err(get_method_error(
+                attributes,
+                overrides,
+                rpc.name.as_str(),
+                message,
+            ));
+        }
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,152 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_metrics_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Metrics", struct_name).as_str()).unwrap()
+}
+
+pub fn wrap_with_metrics(
+    struct_name: &Ident,
+    rpc_name: &str,
+    fn_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let metrics_name = get_metrics_name(struct_name);
+
+    quote::quote! {
+        let started = std::time::Instant::now();
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> = async {
+            #fn_body
+        }
+        .await;
+
+        let status = if result.is_ok() { "ok" } else { "error" };
+
+        #metrics_name::get()
+            .request_duration
+            .with_label_values(&[#rpc_name, status])
+            .observe(started.elapsed().as_secs_f64());
+
+        result
+    }
+}
+
+pub fn generate_count_ping_failure(struct_name: &Ident) -> TokenStream {
+    let metrics_name = get_metrics_name(struct_name);
+    quote::quote!(#metrics_name::get().ping_failures.inc();)
+}
+
+pub fn generate_count_connect(struct_name: &Ident) -> TokenStream {
+    let metrics_name = get_metrics_name(struct_name);
+    quote::quote!(#metrics_name::get().connects.inc();)
+}
+
+// Metric names are the same for all the clients. Client name is a const label, so several clients can be registered
+pub fn generate_metrics_struct(struct_name: &Ident) -> TokenStream {
+    let metrics_name = get_metrics_name(struct_name);
+    let client_name = struct_name.to_string();
+
+    quote::quote! {
+        struct #metrics_name {
+            request_duration: prometheus::HistogramVec,
+            responses: prometheus::IntCounterVec,
+            retries: prometheus::IntCounterVec,
+            hedges: prometheus::IntCounterVec,
+            connects: prometheus::IntCounter,
+            ping_failures: prometheus::IntCounter,
+        }
+
+        impl #metrics_name {
+            fn get() -> &'static Self {
+                static METRICS: std::sync::OnceLock<#metrics_name> = std::sync::OnceLock::new();
+                METRICS.get_or_init(Self::new)
+            }
+
+            // Metrics are already registered (AlreadyReg) if a client with the same name in another module is created first.
+            // Registry does not give the registered collector back, so the first client keeps being exported
+            // and this one counts into its own collectors. Other errors mean the names are taken by metrics with other labels
+            fn register<T: prometheus::core::Collector + Clone + 'static>(
+                registry: &prometheus::Registry,
+                collector: T,
+            ) -> T {
+                match registry.register(Box::new(collector.clone())) {
+                    Ok(()) | Err(prometheus::Error::AlreadyReg) => collector,
+                    Err(err) => panic!("Grpc client metrics can not be registered: {:?}", err),
+                }
+            }
+
+            fn new() -> Self {
+                let registry = prometheus::default_registry();
+
+                let request_duration = prometheus::HistogramVec::new(
+                    prometheus::HistogramOpts::new(
+                        "grpc_client_request_duration_seconds",
+                        "Duration of grpc client calls including retries",
+                    )
+                    .const_label("client", #client_name),
+                    &["rpc", "status"],
+                )
+                .unwrap();
+                let request_duration = Self::register(registry, request_duration);
+
+                let responses = prometheus::IntCounterVec::new(
+                    prometheus::Opts::new(
+                        "grpc_client_responses_total",
+                        "Responses of grpc client attempts by status code and attempt kind",
+                    )
+                    .const_label("client", #client_name),
+                    &["rpc", "code", "attempt"],
+                )
+                .unwrap();
+                let responses = Self::register(registry, responses);
+
+                let retries = prometheus::IntCounterVec::new(
+                    prometheus::Opts::new("grpc_client_retries_total", "Retries of grpc client calls")
+                        .const_label("client", #client_name),
+                    &["rpc"],
+                )
+                .unwrap();
+                let retries = Self::register(registry, retries);
+
+                let hedges = prometheus::IntCounterVec::new(
+                    prometheus::Opts::new("grpc_client_hedges_total", "Hedged attempts of grpc client calls")
+                        .const_label("client", #client_name),
+                    &["rpc"],
+                )
+                .unwrap();
+                let hedges = Self::register(registry, hedges);
+
+                let connects = prometheus::IntCounter::with_opts(
+                    prometheus::Opts::new(
+                        "grpc_client_channel_connects_total",
+                        "Connects and reconnects of grpc client channel",
+                    )
+                    .const_label("client", #client_name),
+                )
+                .unwrap();
+                let connects = Self::register(registry, connects);
+
+                let ping_failures = prometheus::IntCounter::with_opts(
+                    prometheus::Opts::new(
+                        "grpc_client_ping_failures_total",
+                        "Failed background pings of grpc client channel",
+                    )
+                    .const_label("client", #client_name),
+                )
+                .unwrap();
+                let ping_failures = Self::register(registry, ping_failures);
+
+                Self {
+                    request_duration,
+                    responses,
+                    retries,
+                    hedges,
+                    connects,
+                    ping_failures,
+                }
+            }
+        }
+    }
+}
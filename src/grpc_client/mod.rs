@@ -1,9 +1,36 @@
+mod attributes_schema;
+mod call_limits;
+mod client_builder;
+mod client_errors;
+mod channel_source;
+mod client_fields;
+mod client_options;
+mod config_file;
+mod duration_param;
+mod endpoint_health;
+mod executor_call;
+mod failover;
+mod fault_injection;
 mod fn_override;
+mod load_balancing;
+mod method_selection;
+mod metrics;
+mod observed_settings;
 
+mod generate_blocking_client;
+mod generate_client_api;
+mod generate_client_mock;
+mod generate_fake_server;
 mod generate_grpc_methods;
 mod param_type;
+mod payload_logging;
 mod proto_file_reader;
 mod proto_tokens_reader;
+mod record_replay;
+mod response_cache;
+mod shutdown;
+mod single_flight;
+mod trace_spans;
 
 mod generate_interfaces_implementations;
 use generate_grpc_methods::*;
@@ -0,0 +1,53 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+fn get_observed_settings_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}ObservedSettings", struct_name).as_str()).unwrap()
+}
+
+// GrpcChannel asks settings for the url each time it (re)connects
+pub fn generate_observed_settings(
+    struct_name: &Ident,
+    with_metrics: bool,
+    with_tracing: bool,
+) -> TokenStream {
+    let settings_name = get_observed_settings_name(struct_name);
+
+    let count_connect = if with_metrics {
+        super::metrics::generate_count_connect(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    let trace_connect = if with_tracing {
+        super::trace_spans::generate_trace_reconnect(struct_name)
+    } else {
+        quote::quote!()
+    };
+
+    quote::quote! {
+        struct #settings_name {
+            settings: std::sync::Arc<dyn my_grpc_extensions::GrpcClientSettings + Send + Sync + 'static>,
+        }
+
+        #[async_trait::async_trait]
+        impl my_grpc_extensions::GrpcClientSettings for #settings_name {
+            async fn get_grpc_url(&self, name: &'static str) -> String {
+                #count_connect
+                #trace_connect
+                self.settings.get_grpc_url(name).await
+            }
+        }
+    }
+}
+
+pub fn wrap_settings(struct_name: &Ident, settings: TokenStream, observe: bool) -> TokenStream {
+    if !observe {
+        return settings;
+    }
+
+    let settings_name = get_observed_settings_name(struct_name);
+
+    quote::quote!(std::sync::Arc::new(#settings_name { settings: #settings }))
+}
@@ -0,0 +1,481 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::proto_file_reader::into_snake_case;
+
+const DEFAULT_MAX_SIZE: usize = 4096;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PayloadLogging {
+    Off,
+    Metadata,
+    Full,
+}
+
+impl PayloadLogging {
+    pub fn try_read(params: &ParamsList) -> Result<Option<Self>, syn::Error> {
+        let value = match params.try_get_named_param("log_payloads") {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let result = match value.unwrap_as_string_value()?.as_str() {
+            "off" => Self::Off,
+            "metadata" => Self::Metadata,
+            "full" => Self::Full,
+            _ => {
+                return Err(
+                    value.throw_error("log_payloads must be \"off\", \"metadata\" or \"full\"")
+                )
+            }
+        };
+
+        Ok(Some(result))
+    }
+}
+
+pub struct PayloadLoggingSettings {
+    pub verbosity: PayloadLogging,
+    pub redact: Vec<String>,
+    pub max_size: usize,
+}
+
+impl PayloadLoggingSettings {
+    pub fn new(params: &ParamsList) -> Result<Self, syn::Error> {
+        let verbosity = PayloadLogging::try_read(params)?.unwrap_or(PayloadLogging::Off);
+
+        let mut redact = Vec::new();
+
+        if let Some(value) = params.try_get_named_param("redact") {
+            for item in value.unwrap_as_vec()? {
+                redact.push(item.unwrap_as_string_value()?.to_string());
+            }
+        }
+
+        let max_size = match params.try_get_named_param("log_payloads_max_size") {
+            Some(value) => {
+                let max_size = value.unwrap_as_number_value()?.as_usize();
+                if max_size == 0 {
+                    return Err(value.throw_error("log_payloads_max_size must be greater than 0"));
+                }
+                max_size
+            }
+            None => DEFAULT_MAX_SIZE,
+        };
+
+        Ok(Self {
+            verbosity,
+            redact,
+            max_size,
+        })
+    }
+}
+
+pub fn get_payload_logger_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}PayloadLogger", struct_name).as_str()).unwrap()
+}
+
+pub fn wrap_with_payload_logging(
+    struct_name: &Ident,
+    rpc_name: &str,
+    verbosity: PayloadLogging,
+    fn_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let logger_name = get_payload_logger_name(struct_name);
+    let service_name = struct_name.to_string();
+
+    let (format_request, output_binding, log_ok, log_err) = match verbosity {
+        PayloadLogging::Off => return fn_body,
+        PayloadLogging::Metadata => (
+            quote::quote!(),
+            quote::quote!(_),
+            quote::quote! {
+                tracing::info!(
+                    service = #service_name,
+                    rpc = #rpc_name,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    "Grpc call succeeded"
+                );
+            },
+            quote::quote! {
+                tracing::warn!(
+                    service = #service_name,
+                    rpc = #rpc_name,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    "Grpc call failed: {:?}",
+                    err
+                );
+            },
+        ),
+        PayloadLogging::Full => (
+            quote::quote!(let request_payload = #logger_name::format(&input_data);),
+            quote::quote!(output),
+            quote::quote! {
+                tracing::info!(
+                    service = #service_name,
+                    rpc = #rpc_name,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    request = request_payload.as_str(),
+                    response = #logger_name::format(output).as_str(),
+                    "Grpc call succeeded"
+                );
+            },
+            quote::quote! {
+                tracing::warn!(
+                    service = #service_name,
+                    rpc = #rpc_name,
+                    duration_ms = started.elapsed().as_millis() as u64,
+                    request = request_payload.as_str(),
+                    "Grpc call failed: {:?}",
+                    err
+                );
+            },
+        ),
+    };
+
+    quote::quote! {
+        #format_request
+
+        let started = std::time::Instant::now();
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> = async {
+            #fn_body
+        }
+        .await;
+
+        match &result {
+            Ok(#output_binding) => {
+                #log_ok
+            }
+            Err(err) => {
+                #log_err
+            }
+        }
+
+        result
+    }
+}
+
+// Functions are compiled for the tests of this file and quoted into the generated client,
+// so the code which masks the payloads is the tested one
+macro_rules! redaction_functions {
+    ($($tokens:tt)*) => {
+        #[cfg(test)]
+        mod payload_redaction {
+            $($tokens)*
+        }
+
+        fn generate_redaction_functions() -> TokenStream {
+            quote::quote!($($tokens)*)
+        }
+    };
+}
+
+redaction_functions! {
+    // Masks the values of the fields with the given names in Debug output of a payload.
+    // Fields are found at any depth. Values are skipped up to the next field or the closing bracket,
+    // so custom Debug output of the value is masked as a whole even if it has "," inside
+    pub fn redact_debug(src: &str, fields: &[&str]) -> String {
+        let mut result = String::with_capacity(src.len());
+        let mut pos = 0;
+
+        'chars: while pos < src.len() {
+            let ch = src[pos..].chars().next().unwrap();
+
+            if ch == '"' {
+                let end = skip_string(src, pos);
+                result.push_str(&src[pos..end]);
+                pos = end;
+                continue;
+            }
+
+            if ch == '\'' {
+                if let Some(end) = skip_char(src, pos) {
+                    result.push_str(&src[pos..end]);
+                    pos = end;
+                    continue;
+                }
+            }
+
+            let is_field_start = !result.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+
+            if is_field_start {
+                for field in fields {
+                    if src[pos..].starts_with(field) && src[pos + field.len()..].starts_with(": ") {
+                        result.push_str(field);
+                        result.push_str(": \"***\"");
+                        pos = skip_debug_value(src, pos + field.len() + 2);
+                        continue 'chars;
+                    }
+                }
+            }
+
+            result.push(ch);
+            pos += ch.len_utf8();
+        }
+
+        result
+    }
+
+    // Returns the position of ", next_field: " or the closing bracket which ends the value started at start.
+    // Anything else is treated as a part of the value, so unclear output is masked rather than logged
+    pub fn skip_debug_value(src: &str, start: usize) -> usize {
+        let mut depth = 0;
+        let mut pos = start;
+
+        while pos < src.len() {
+            let ch = src[pos..].chars().next().unwrap();
+
+            match ch {
+                '"' => {
+                    pos = skip_string(src, pos);
+                    continue;
+                }
+                '\'' => {
+                    if let Some(end) = skip_char(src, pos) {
+                        pos = end;
+                        continue;
+                    }
+                }
+                '(' | '[' | '{' => depth += 1,
+                ',' if depth == 0 && is_next_field(&src[pos + 1..]) => return pos,
+                ')' | ']' | '}' if depth == 0 => return pos,
+                ')' | ']' | '}' => depth -= 1,
+                _ => {}
+            }
+
+            pos += ch.len_utf8();
+        }
+
+        src.len()
+    }
+
+    fn is_next_field(src: &str) -> bool {
+        let src = match src.strip_prefix(' ') {
+            Some(src) => src,
+            None => return false,
+        };
+
+        let name_len = src
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(src.len());
+
+        name_len > 0 && src[name_len..].starts_with(": ")
+    }
+
+    // String which is not closed runs to the end of the output
+    fn skip_string(src: &str, start: usize) -> usize {
+        let mut escaped = false;
+
+        for (index, ch) in src[start + 1..].char_indices() {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => return start + 1 + index + 1,
+                _ => {}
+            }
+        }
+
+        src.len()
+    }
+
+    // Char literal like '"' or '\'' is skipped as a whole, so the quote inside does not start a string
+    fn skip_char(src: &str, start: usize) -> Option<usize> {
+        let mut chars = src[start + 1..].char_indices();
+
+        let (_, ch) = chars.next()?;
+
+        if ch == '\\' {
+            for (index, ch) in chars.take(10) {
+                if ch == '\'' {
+                    return Some(start + 1 + index + 1);
+                }
+            }
+
+            return None;
+        }
+
+        match chars.next()? {
+            (index, '\'') => Some(start + 1 + index + 1),
+            _ => None,
+        }
+    }
+
+    pub fn truncate_payload(mut src: String, max_size: usize) -> String {
+        if src.len() <= max_size {
+            return src;
+        }
+
+        let full_size = src.len();
+
+        let mut size = max_size;
+        while !src.is_char_boundary(size) {
+            size -= 1;
+        }
+
+        src.truncate(size);
+        src.push_str(format!("... ({} bytes)", full_size).as_str());
+        src
+    }
+}
+
+fn get_payload_redaction_module(struct_name: &Ident) -> (TokenStream, TokenStream) {
+    let module_name = TokenStream::from_str(
+        format!("{}_payload_redaction", into_snake_case(struct_name.to_string().as_str())).as_str(),
+    )
+    .unwrap();
+
+    let functions = generate_redaction_functions();
+
+    let module = quote::quote! {
+        mod #module_name {
+            #functions
+        }
+    };
+
+    (module_name, module)
+}
+
+// Payloads are logged as Debug output. Redacted fields are found by name in it, so nested messages are masked too
+pub fn generate_payload_logger_struct(
+    struct_name: &Ident,
+    settings: &PayloadLoggingSettings,
+) -> TokenStream {
+    let logger_name = get_payload_logger_name(struct_name);
+    let redact = &settings.redact;
+    let max_size = proc_macro2::Literal::usize_unsuffixed(settings.max_size);
+    let (module_name, module) = get_payload_redaction_module(struct_name);
+
+    quote::quote! {
+        #module
+
+        struct #logger_name;
+
+        impl #logger_name {
+            const REDACT: &'static [&'static str] = &[#(#redact),*];
+            const MAX_SIZE: usize = #max_size;
+
+            fn format(value: &impl std::fmt::Debug) -> String {
+                let result = #module_name::redact_debug(format!("{:?}", value).as_str(), Self::REDACT);
+                #module_name::truncate_payload(result, Self::MAX_SIZE)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::payload_redaction::*;
+
+    const SECRET: &str = "s3cr3t";
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Card {
+        card_number: String,
+        holder: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Request {
+        login: String,
+        password: String,
+        note: String,
+        cards: Vec<Card>,
+        limit: Option<i64>,
+    }
+
+    struct Token(String);
+
+    impl std::fmt::Debug for Token {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Token <{}, {}> )]", self.0, self.0)
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct WithCustomDebug {
+        quote: char,
+        password: Token,
+        tail: Token,
+    }
+
+    fn redact(value: &impl std::fmt::Debug) -> String {
+        redact_debug(format!("{:?}", value).as_str(), &["password", "card_number"])
+    }
+
+    #[test]
+    fn test_nested_messages() {
+        let request = Request {
+            login: "user".to_string(),
+            password: SECRET.to_string(),
+            note: "password: x, card_number: y".to_string(),
+            cards: vec![Card {
+                card_number: SECRET.to_string(),
+                holder: "John".to_string(),
+            }],
+            limit: Some(10),
+        };
+
+        let result = redact(&request);
+
+        assert!(!result.contains(SECRET));
+        assert_eq!(
+            result,
+            r#"Request { login: "user", password: "***", note: "password: x, card_number: y", cards: [Card { card_number: "***", holder: "John" }], limit: Some(10) }"#
+        );
+    }
+
+    #[test]
+    fn test_strings_with_separators() {
+        let request = Request {
+            login: "a\", password: \"b".to_string(),
+            password: format!("{}, x: {{ \" }}", SECRET),
+            note: String::new(),
+            cards: vec![],
+            limit: None,
+        };
+
+        let result = redact(&request);
+
+        assert!(!result.contains(SECRET));
+        assert!(result.contains(r#"login: "a\", password: \"b""#));
+        assert!(result.contains(r#"password: "***", note: """#));
+    }
+
+    #[test]
+    fn test_custom_debug() {
+        let value = WithCustomDebug {
+            quote: '"',
+            password: Token(SECRET.to_string()),
+            tail: Token("visible".to_string()),
+        };
+
+        let result = redact(&value);
+
+        assert!(!result.contains(SECRET));
+        assert!(result.starts_with(r#"WithCustomDebug { quote: '"', password: "***""#));
+    }
+
+    #[test]
+    fn test_generated_functions() {
+        let functions = super::generate_redaction_functions()
+            .into_iter()
+            .filter(|token| token.to_string() == "fn")
+            .count();
+
+        assert_eq!(functions, 6);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate_payload("abc".to_string(), 3), "abc");
+        assert_eq!(truncate_payload("abcd".to_string(), 3), "abc... (4 bytes)");
+        assert_eq!(truncate_payload("aéb".to_string(), 2), "a... (4 bytes)");
+    }
+}
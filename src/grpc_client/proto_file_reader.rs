@@ -5,11 +5,12 @@ use std::{
 
 use super::{proto_tokens_reader::ProtoTokensReader, ParamType};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProtoRpc {
     pub name: String,
     input_param: String,
     output_param: String,
+    pub rename_to: Option<String>,
 }
 
 impl ProtoRpc {
@@ -17,6 +18,14 @@ impl ProtoRpc {
         proc_macro2::TokenStream::from_str(&into_snake_case(self.name.as_str())).unwrap()
     }
 
+    // Name of the method of the generated client. Tonic client and server keep the proto based name
+    pub fn get_client_fn_name_as_token(&self) -> proc_macro2::TokenStream {
+        match &self.rename_to {
+            Some(rename_to) => proc_macro2::TokenStream::from_str(rename_to.as_str()).unwrap(),
+            None => self.get_fn_name_as_token(),
+        }
+    }
+
     pub fn get_input_param(&self) -> Option<ParamType> {
         ParamType::parse(&self.input_param)
     }
@@ -24,9 +33,23 @@ impl ProtoRpc {
     pub fn get_output_param(&self) -> Option<ParamType> {
         ParamType::parse(&self.output_param)
     }
+
+    pub fn is_unary(&self) -> bool {
+        let input_is_stream = match self.get_input_param() {
+            Some(param) => param.is_stream(),
+            None => false,
+        };
+
+        let output_is_stream = match self.get_output_param() {
+            Some(param) => param.is_stream(),
+            None => false,
+        };
+
+        !input_is_stream && !output_is_stream
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProtoServiceDescription {
     pub service_name: String,
     pub rpc: Vec<ProtoRpc>,
@@ -37,14 +60,24 @@ impl ProtoServiceDescription {
         proc_macro2::TokenStream::from_str(&self.service_name).unwrap()
     }
 
+    pub fn get_server_name(&self) -> &str {
+        self.service_name
+            .strip_suffix("Client")
+            .unwrap_or(self.service_name.as_str())
+    }
+
     pub fn has_method(&self, method_name: &str) -> bool {
+        self.get_method(method_name).is_some()
+    }
+
+    pub fn get_method(&self, method_name: &str) -> Option<&ProtoRpc> {
         for rpc in &self.rpc {
             if rpc.name == method_name {
-                return true;
+                return Some(rpc);
             }
         }
 
-        false
+        None
     }
 
     pub fn read_proto_file(file_name: &str) -> Self {
@@ -133,6 +166,7 @@ impl ProtoServiceDescription {
                                     name: name.to_string(),
                                     input_param: input_param_name.to_string(),
                                     output_param: out_param_name.to_string(),
+                                    rename_to: None,
                                 });
                             }
                             current_token = CurrentToken::None;
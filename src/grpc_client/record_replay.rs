@@ -0,0 +1,364 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+use super::ParamType;
+
+pub fn get_record_replay_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}RecordReplay", struct_name).as_str()).unwrap()
+}
+
+fn get_replayed_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Replayed", struct_name).as_str()).unwrap()
+}
+
+fn generate_encode(param: Option<&ParamType<'_>>, is_output: bool, value: TokenStream) -> TokenStream {
+    match param {
+        Some(ParamType::Stream(_)) => {
+            if is_output {
+                quote::quote! {
+                    match #value {
+                        Some(items) => {
+                            let mut result = vec![1u8];
+                            for item in items {
+                                result.extend(prost::Message::encode_length_delimited_to_vec(item));
+                            }
+                            result
+                        }
+                        None => vec![0u8],
+                    }
+                }
+            } else {
+                quote::quote! {
+                    {
+                        let mut result = Vec::new();
+                        for item in #value {
+                            result.extend(prost::Message::encode_length_delimited_to_vec(item));
+                        }
+                        result
+                    }
+                }
+            }
+        }
+        _ => quote::quote!(prost::Message::encode_to_vec(#value)),
+    }
+}
+
+// Decoding returns an error, so a file which does not match the proto any more fails the call instead of panicking
+fn generate_decode_output(output_param: Option<&ParamType<'_>>) -> TokenStream {
+    match output_param {
+        Some(ParamType::Stream(name)) => {
+            let item_type = TokenStream::from_str(name).unwrap();
+            quote::quote! {
+                (|| -> Result<_, prost::DecodeError> {
+                    if output.first() != Some(&1) {
+                        return Ok(None);
+                    }
+
+                    let mut buffer = &output[1..];
+                    let mut items = Vec::new();
+                    while !buffer.is_empty() {
+                        items.push(<#item_type as prost::Message>::decode_length_delimited(&mut buffer)?);
+                    }
+                    Ok(Some(items))
+                })()
+            }
+        }
+        Some(ParamType::Single(name)) => {
+            let output_type = TokenStream::from_str(name).unwrap();
+            quote::quote!(<#output_type as prost::Message>::decode(output.as_slice()))
+        }
+        None => quote::quote! {
+            {
+                let _ = output;
+                Ok::<_, prost::DecodeError>(())
+            }
+        },
+    }
+}
+
+// In record mode every call is appended to the file. In replay mode responses are served from the file
+// and requests which were not recorded fail and are remembered.
+pub fn wrap_with_record_replay(
+    struct_name: &Ident,
+    rpc_name: &str,
+    input_param: Option<&ParamType<'_>>,
+    output_param: Option<&ParamType<'_>>,
+    fn_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let replayed_name = get_replayed_name(struct_name);
+    let error_name = super::client_errors::get_client_error_name(struct_name);
+
+    let encode_input = generate_encode(input_param, false, quote::quote!(&input_data));
+    let encode_output = generate_encode(output_param, true, quote::quote!(output));
+    let decode_output = generate_decode_output(output_param);
+
+    quote::quote! {
+        let record_replay_input = if self.record_replay.is_off() {
+            None
+        } else {
+            Some(#encode_input)
+        };
+
+        if let Some(input) = &record_replay_input {
+            match self.record_replay.try_replay(#rpc_name, input) {
+                Some(#replayed_name::Output(output)) => {
+                    return match #decode_output {
+                        Ok(output) => Ok(output),
+                        Err(err) => Err(tonic::Status::data_loss(format!(
+                            "Recorded response of {} can not be decoded: {}",
+                            #rpc_name, err
+                        ))
+                        .into()),
+                    }
+                }
+                Some(#replayed_name::Error(code, message)) => {
+                    return Err(tonic::Status::new(tonic::Code::from_i32(code), message).into())
+                }
+                Some(#replayed_name::NotRecorded) => {
+                    return Err(tonic::Status::not_found(format!("Request to {} is not recorded", #rpc_name)).into())
+                }
+                None => {}
+            }
+        }
+
+        let started = std::time::Instant::now();
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> = async {
+            #fn_body
+        }
+        .await;
+
+        if let Some(input) = record_replay_input {
+            let output = match &result {
+                Ok(output) => Ok(#encode_output),
+                Err(err) => {
+                    let status = #error_name::status_of(err);
+                    Err((status.code() as i32, status.message().to_string()))
+                }
+            };
+
+            self.record_replay.record(#rpc_name, input, output, started.elapsed());
+        }
+
+        result
+    }
+}
+
+pub fn generate_record_replay_client_methods(vis: &syn::Visibility) -> TokenStream {
+    quote::quote! {
+        #vis fn start_recording(&self, file_name: &str) -> std::io::Result<()> {
+            self.record_replay.start_recording(file_name)
+        }
+
+        #vis fn start_replay(&self, file_name: &str) -> std::io::Result<()> {
+            self.record_replay.start_replay(file_name)
+        }
+
+        #vis fn stop_record_replay(&self) {
+            self.record_replay.stop();
+        }
+
+        #vis fn get_unrecorded_requests(&self) -> Vec<String> {
+            self.record_replay.get_unrecorded_requests()
+        }
+    }
+}
+
+// File has one call per line: rpc name, hex encoded input, OK or ERR:{status code},
+// hex encoded output or error message, duration in microseconds
+pub fn generate_record_replay_struct(struct_name: &Ident) -> TokenStream {
+    let record_replay_name = get_record_replay_name(struct_name);
+    let replayed_name = get_replayed_name(struct_name);
+    let mode_name =
+        TokenStream::from_str(format!("{}RecordReplayMode", struct_name).as_str()).unwrap();
+
+    quote::quote! {
+        enum #replayed_name {
+            Output(Vec<u8>),
+            Error(i32, String),
+            NotRecorded,
+        }
+
+        enum #mode_name {
+            Off,
+            Record(std::fs::File),
+            Replay {
+                calls: std::collections::HashMap<(String, String), std::collections::VecDeque<Result<Vec<u8>, (i32, String)>>>,
+                unrecorded: Vec<String>,
+            },
+        }
+
+        struct #record_replay_name {
+            mode: std::sync::Mutex<#mode_name>,
+        }
+
+        impl #record_replay_name {
+            fn new() -> Self {
+                Self {
+                    mode: std::sync::Mutex::new(#mode_name::Off),
+                }
+            }
+
+            fn to_hex(src: &[u8]) -> String {
+                let mut result = String::with_capacity(src.len() * 2);
+                for b in src {
+                    result.push_str(format!("{:02x}", b).as_str());
+                }
+                result
+            }
+
+            fn from_hex(src: &str) -> Option<Vec<u8>> {
+                if src.len() % 2 != 0 {
+                    return None;
+                }
+
+                (0..src.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(src.get(i..i + 2)?, 16).ok())
+                    .collect()
+            }
+
+            fn parse_output(status: &str, output: &str) -> Option<Result<Vec<u8>, (i32, String)>> {
+                let output = Self::from_hex(output)?;
+
+                if status == "OK" {
+                    return Some(Ok(output));
+                }
+
+                let code = status.strip_prefix("ERR:")?.parse().ok()?;
+
+                Some(Err((code, String::from_utf8(output).ok()?)))
+            }
+
+            fn is_off(&self) -> bool {
+                matches!(*self.mode.lock().unwrap(), #mode_name::Off)
+            }
+
+            fn start_recording(&self, file_name: &str) -> std::io::Result<()> {
+                let file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(file_name)?;
+
+                *self.mode.lock().unwrap() = #mode_name::Record(file);
+                Ok(())
+            }
+
+            // Mode is not changed if the file can not be read or has an invalid call
+            fn start_replay(&self, file_name: &str) -> std::io::Result<()> {
+                let content = std::fs::read_to_string(file_name)?;
+
+                let mut calls = std::collections::HashMap::new();
+
+                for (line_no, line) in content.lines().enumerate() {
+                    let parts: Vec<&str> = line.split('\t').collect();
+                    if parts.len() < 4 {
+                        continue;
+                    }
+
+                    let output = match Self::parse_output(parts[2], parts[3]) {
+                        Some(output) => output,
+                        None => {
+                            return Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!(
+                                    "Replay file {} has invalid call at line {}",
+                                    file_name,
+                                    line_no + 1
+                                ),
+                            ))
+                        }
+                    };
+
+                    calls
+                        .entry((parts[0].to_string(), parts[1].to_string()))
+                        .or_insert_with(std::collections::VecDeque::new)
+                        .push_back(output);
+                }
+
+                *self.mode.lock().unwrap() = #mode_name::Replay {
+                    calls,
+                    unrecorded: Vec::new(),
+                };
+
+                Ok(())
+            }
+
+            fn stop(&self) {
+                *self.mode.lock().unwrap() = #mode_name::Off;
+            }
+
+            fn try_replay(&self, rpc_name: &str, input: &[u8]) -> Option<#replayed_name> {
+                let mut mode = self.mode.lock().unwrap();
+
+                let (calls, unrecorded) = match &mut *mode {
+                    #mode_name::Replay { calls, unrecorded } => (calls, unrecorded),
+                    _ => return None,
+                };
+
+                let key = (rpc_name.to_string(), Self::to_hex(input));
+
+                // The last recorded response is served again if the request is repeated more times than it was recorded
+                let output = match calls.get_mut(&key) {
+                    Some(outputs) if outputs.len() > 1 => outputs.pop_front(),
+                    Some(outputs) => outputs.front().cloned(),
+                    None => None,
+                };
+
+                match output {
+                    Some(Ok(output)) => Some(#replayed_name::Output(output)),
+                    Some(Err((code, message))) => Some(#replayed_name::Error(code, message)),
+                    None => {
+                        unrecorded.push(format!("{} {}", key.0, key.1));
+                        Some(#replayed_name::NotRecorded)
+                    }
+                }
+            }
+
+            fn record(
+                &self,
+                rpc_name: &str,
+                input: Vec<u8>,
+                output: Result<Vec<u8>, (i32, String)>,
+                duration: std::time::Duration,
+            ) {
+                use std::io::Write;
+
+                let mut mode = self.mode.lock().unwrap();
+
+                if let #mode_name::Record(file) = &mut *mode {
+                    let (status, output) = match output {
+                        Ok(output) => ("OK".to_string(), Self::to_hex(&output)),
+                        Err((code, message)) => {
+                            (format!("ERR:{}", code), Self::to_hex(message.as_bytes()))
+                        }
+                    };
+
+                    // Failed write loses the call from the recording, but does not fail the call itself
+                    let written = writeln!(
+                        file,
+                        "{}\t{}\t{}\t{}\t{}",
+                        rpc_name,
+                        Self::to_hex(&input),
+                        status,
+                        output,
+                        duration.as_micros()
+                    );
+
+                    if let Err(err) = written {
+                        tracing::warn!(rpc = rpc_name, "Call can not be recorded: {:?}", err);
+                    }
+                }
+            }
+
+            fn get_unrecorded_requests(&self) -> Vec<String> {
+                match &*self.mode.lock().unwrap() {
+                    #mode_name::Replay { unrecorded, .. } => unrecorded.clone(),
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::{str::FromStr, time::Duration};
+
+use proc_macro2::{Ident, TokenStream};
+use types_reader::ParamsList;
+
+use super::duration_param::read_duration;
+
+pub struct CacheOverride {
+    pub ttl: Duration,
+    pub max_entries: usize,
+}
+
+impl CacheOverride {
+    pub fn new(item: &ParamsList) -> Result<Option<Self>, syn::Error> {
+        let cache = item.try_get_named_param("cache");
+
+        if cache.is_none() {
+            return Ok(None);
+        }
+
+        let cache = cache.unwrap().unwrap_as_single_object()?;
+
+        let result = Self {
+            ttl: read_duration(cache, "ttl", "ttl_ms", Duration::from_millis)?,
+            max_entries: cache
+                .get_named_param("max_entries")?
+                .unwrap_as_number_value()?
+                .as_usize(),
+        };
+
+        if result.ttl.is_zero() || result.max_entries == 0 {
+            return Err(syn::Error::new_spanned(
+                cache.get_token_stream().clone(),
+                "ttl and max_entries must be greater than 0",
+            ));
+        }
+
+        Ok(Some(result))
+    }
+
+    pub fn get_field_type(
+        &self,
+        cache_name: &TokenStream,
+        output_data_type: &TokenStream,
+    ) -> TokenStream {
+        quote::quote!(#cache_name<#output_data_type>)
+    }
+
+    pub fn get_field_initializer(&self, cache_name: &TokenStream) -> TokenStream {
+        let ttl = super::duration_param::duration_to_tokens(self.ttl);
+        let max_entries = proc_macro2::Literal::usize_unsuffixed(self.max_entries);
+        quote::quote!(#cache_name::new(#ttl, #max_entries))
+    }
+}
+
+pub fn get_response_cache_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}ResponseCache", struct_name).as_str()).unwrap()
+}
+
+// Wraps the call so the response is taken from the cache if it is there and put into the cache otherwise
+pub fn wrap_with_cache(
+    cache_field: &TokenStream,
+    telemetry_event_name: &str,
+    with_telemetry: bool,
+    call_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let (report_hit, report_miss) = if with_telemetry {
+        (
+            generate_telemetry_event(telemetry_event_name, "Hit"),
+            generate_telemetry_event(telemetry_event_name, "Miss"),
+        )
+    } else {
+        (quote::quote!(), quote::quote!())
+    };
+
+    quote::quote! {
+        let cache_key = prost::Message::encode_to_vec(&input_data);
+
+        if let Some(result) = self.#cache_field.get(&cache_key) {
+            #report_hit
+            return Ok(result);
+        }
+
+        #report_miss
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> = async {
+            #call_body
+        }
+        .await;
+
+        if let Ok(result) = &result {
+            self.#cache_field.insert(cache_key, result.clone());
+        }
+
+        result
+    }
+}
+
+fn generate_telemetry_event(event_name: &str, result: &str) -> TokenStream {
+    quote::quote! {
+        my_telemetry::TELEMETRY_INTERFACE
+            .write_success(
+                ctx,
+                rust_extensions::date_time::DateTimeAsMicroseconds::now(),
+                #event_name.to_string(),
+                #result.to_string(),
+                None,
+            )
+            .await;
+    }
+}
+
+// Entries expire by the tokio clock, so paused time of tests applies to the ttl
+pub fn generate_response_cache_struct(cache_name: &TokenStream) -> TokenStream {
+    quote::quote! {
+        struct #cache_name<TResponse: Clone> {
+            ttl: std::time::Duration,
+            max_entries: usize,
+            items: std::sync::Mutex<std::collections::HashMap<Vec<u8>, (tokio::time::Instant, TResponse)>>,
+        }
+
+        impl<TResponse: Clone> #cache_name<TResponse> {
+            fn new(ttl: std::time::Duration, max_entries: usize) -> Self {
+                Self {
+                    ttl,
+                    max_entries,
+                    items: std::sync::Mutex::new(std::collections::HashMap::new()),
+                }
+            }
+
+            fn get(&self, key: &[u8]) -> Option<TResponse> {
+                let items = self.items.lock().unwrap();
+                let (created, value) = items.get(key)?;
+
+                if created.elapsed() >= self.ttl {
+                    return None;
+                }
+
+                Some(value.clone())
+            }
+
+            fn insert(&self, key: Vec<u8>, value: TResponse) {
+                let mut items = self.items.lock().unwrap();
+
+                if items.len() >= self.max_entries && !items.contains_key(&key) {
+                    let ttl = self.ttl;
+                    items.retain(|_, (created, _)| created.elapsed() < ttl);
+
+                    if items.len() >= self.max_entries {
+                        let oldest = items
+                            .iter()
+                            .min_by_key(|(_, (created, _))| *created)
+                            .map(|(key, _)| key.clone());
+
+                        if let Some(oldest) = oldest {
+                            items.remove(&oldest);
+                        }
+                    }
+                }
+
+                items.insert(key, (tokio::time::Instant::now(), value));
+            }
+
+            fn clear(&self) {
+                self.items.lock().unwrap().clear();
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_shutdown_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}Shutdown", struct_name).as_str()).unwrap()
+}
+
+// Calls are counted, so shutdown can wait for the ones which are in flight
+pub fn generate_shutdown_struct(struct_name: &Ident) -> TokenStream {
+    let shutdown_name = get_shutdown_name(struct_name);
+    let call_guard_name =
+        TokenStream::from_str(format!("{}CallGuard", struct_name).as_str()).unwrap();
+    let client_name = struct_name.to_string();
+
+    let return_shut_down = super::client_errors::generate_return_client_error(
+        struct_name,
+        quote::quote!(ShutDown),
+        quote::quote!(format!("{} is not called. {} is shut down", rpc_name, #client_name)),
+    );
+
+    quote::quote! {
+        struct #call_guard_name<'s>(&'s #shutdown_name);
+
+        impl<'s> Drop for #call_guard_name<'s> {
+            fn drop(&mut self) {
+                if self.0.in_flight.fetch_sub(1, std::sync::atomic::Ordering::SeqCst) == 1 {
+                    self.0.drained.notify_waiters();
+                }
+            }
+        }
+
+        struct #shutdown_name {
+            is_shut_down: std::sync::atomic::AtomicBool,
+            in_flight: std::sync::atomic::AtomicUsize,
+            drained: tokio::sync::Notify,
+        }
+
+        impl #shutdown_name {
+            fn new() -> Self {
+                Self {
+                    is_shut_down: std::sync::atomic::AtomicBool::new(false),
+                    in_flight: std::sync::atomic::AtomicUsize::new(0),
+                    drained: tokio::sync::Notify::new(),
+                }
+            }
+
+            fn is_shut_down(&self) -> bool {
+                self.is_shut_down.load(std::sync::atomic::Ordering::SeqCst)
+            }
+
+            // Call is counted before the flag is checked, so shutdown can not miss a call which passed the check
+            fn start_call(
+                &self,
+                rpc_name: &str,
+            ) -> Result<#call_guard_name<'_>, my_grpc_extensions::GrpcReadError> {
+                self.in_flight.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let guard = #call_guard_name(self);
+
+                if self.is_shut_down() {
+                    #return_shut_down
+                }
+
+                Ok(guard)
+            }
+
+            async fn shutdown(&self, drain_timeout: std::time::Duration) -> bool {
+                self.is_shut_down
+                    .store(true, std::sync::atomic::Ordering::SeqCst);
+
+                let drain = async {
+                    loop {
+                        let drained = self.drained.notified();
+
+                        if self.in_flight.load(std::sync::atomic::Ordering::SeqCst) == 0 {
+                            return;
+                        }
+
+                        drained.await;
+                    }
+                };
+
+                tokio::time::timeout(drain_timeout, drain).await.is_ok()
+            }
+        }
+    }
+}
+
+pub fn wrap_with_shutdown_guard(rpc_name: &str, fn_body: TokenStream) -> TokenStream {
+    quote::quote! {
+        let _call_guard = self.shutdown.start_call(#rpc_name)?;
+        #fn_body
+    }
+}
+
+pub fn get_channel_slot_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}ChannelSlot", struct_name).as_str()).unwrap()
+}
+
+// Client drops its channels once it is shut down, so the connections are closed together with GrpcChannel.
+// Calls which are still in flight keep their channel until they are finished.
+// Slot keeps the way its channel is made, so reconnect replaces the channel with a new one
+pub fn generate_channel_slot(struct_name: &Ident) -> TokenStream {
+    let slot_name = get_channel_slot_name(struct_name);
+
+    quote::quote! {
+        struct #slot_name {
+            channel: std::sync::RwLock<Option<std::sync::Arc<my_grpc_extensions::GrpcChannel<TGrpcService>>>>,
+            connect: Box<dyn Fn() -> my_grpc_extensions::GrpcChannel<TGrpcService> + Send + Sync>,
+        }
+
+        impl #slot_name {
+            fn new(
+                connect: impl Fn() -> my_grpc_extensions::GrpcChannel<TGrpcService> + Send + Sync + 'static,
+            ) -> Self {
+                Self {
+                    channel: std::sync::RwLock::new(Some(std::sync::Arc::new(connect()))),
+                    connect: Box::new(connect),
+                }
+            }
+
+            fn get(&self) -> Option<std::sync::Arc<my_grpc_extensions::GrpcChannel<TGrpcService>>> {
+                self.channel.read().unwrap_or_else(|err| err.into_inner()).clone()
+            }
+
+            // Channel of a closed slot is not made again
+            fn reconnect(&self) {
+                let mut channel = self.channel.write().unwrap_or_else(|err| err.into_inner());
+
+                if channel.is_some() {
+                    *channel = Some(std::sync::Arc::new((self.connect)()));
+                }
+            }
+
+            fn close(&self) {
+                self.channel.write().unwrap_or_else(|err| err.into_inner()).take();
+            }
+        }
+    }
+}
+
+// Returns the expression which makes a channel slot. Values of the factory fields are cloned for every channel
+pub fn generate_new_channel_slot(
+    struct_name: &Ident,
+    settings: &TokenStream,
+    factory_fields: &[(TokenStream, TokenStream)],
+    timings: &TokenStream,
+) -> TokenStream {
+    let slot_name = get_channel_slot_name(struct_name);
+    let field_names: Vec<&TokenStream> = factory_fields.iter().map(|(name, _)| name).collect();
+    let field_values = factory_fields.iter().map(|(_, value)| value);
+
+    quote::quote! {
+        {
+            let settings = #settings;
+            #(let #field_names = #field_values.clone();)*
+            let timings = #timings;
+
+            #slot_name::new(move || {
+                my_grpc_extensions::GrpcChannel::new(
+                    settings.clone(),
+                    std::sync::Arc::new(MyGrpcServiceFactory {
+                        #(#field_names: #field_names.clone(),)*
+                    }),
+                    timings.request_timeout,
+                    timings.ping_timeout,
+                    timings.ping_interval,
+                )
+            })
+        }
+    }
+}
+
+// Returns the client error for a call which passed the shutdown check but found the channels closed
+pub fn generate_return_channel_closed(struct_name: &Ident) -> TokenStream {
+    let client_name = struct_name.to_string();
+
+    super::client_errors::generate_return_client_error(
+        struct_name,
+        quote::quote!(ShutDown),
+        quote::quote!(format!("{} is shut down. Channel is closed", #client_name)),
+    )
+}
+
+// Channel of a shut down client is dropped, so the pings which are still scheduled are skipped
+pub fn generate_stop_ping_on_shutdown() -> TokenStream {
+    quote::quote! {
+        if self.shutdown.is_shut_down() {
+            return;
+        }
+    }
+}
+
+pub fn generate_shutdown_methods(vis: &syn::Visibility, close_channels: &TokenStream) -> TokenStream {
+    quote::quote! {
+        // New calls get UNAVAILABLE status marked as ShutDown client error. Channels are dropped after the drain.
+        // Returns false if some calls are still in flight after drain_timeout
+        #vis async fn shutdown(&self, drain_timeout: std::time::Duration) -> bool {
+            let drained = self.shutdown.shutdown(drain_timeout).await;
+            #close_channels
+            drained
+        }
+
+        #vis fn is_shut_down(&self) -> bool {
+            self.shutdown.is_shut_down()
+        }
+    }
+}
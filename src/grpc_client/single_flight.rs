@@ -0,0 +1,141 @@
+use std::str::FromStr;
+
+use proc_macro2::{Ident, TokenStream};
+
+pub fn get_single_flight_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}SingleFlight", struct_name).as_str()).unwrap()
+}
+
+fn get_single_flight_guard_name(struct_name: &Ident) -> TokenStream {
+    TokenStream::from_str(format!("{}SingleFlightGuard", struct_name).as_str()).unwrap()
+}
+
+// First caller with the given request executes it. Callers which come while it is in flight wait for its result.
+// Errors are shared the same way. If the first caller is cancelled - waiting callers execute the request themselves.
+pub fn wrap_with_single_flight(
+    struct_name: &Ident,
+    single_flight_field: &TokenStream,
+    call_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let guard_name = get_single_flight_guard_name(struct_name);
+    let error_name = super::client_errors::get_client_error_name(struct_name);
+
+    quote::quote! {
+        let single_flight_key = prost::Message::encode_to_vec(&input_data);
+
+        let mut single_flight_guard = None;
+
+        match self.#single_flight_field.join(&single_flight_key) {
+            Some(waiter) => match waiter.await {
+                Ok(Some(Ok(result))) => return Ok(result),
+                Ok(Some(Err(status))) => return Err(status.into()),
+                _ => {}
+            },
+            None => {
+                single_flight_guard = Some(#guard_name::new(&self.#single_flight_field, single_flight_key));
+            }
+        }
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> = async {
+            #call_body
+        }
+        .await;
+
+        if let Some(guard) = &mut single_flight_guard {
+            match &result {
+                Ok(result) => guard.set_result(Ok(result.clone())),
+                Err(err) => guard.set_result(Err(#error_name::status_of(err))),
+            }
+        }
+
+        result
+    }
+}
+
+pub fn generate_single_flight_struct(struct_name: &Ident) -> TokenStream {
+    let single_flight_name = get_single_flight_name(struct_name);
+    let guard_name = get_single_flight_guard_name(struct_name);
+
+    quote::quote! {
+        struct #single_flight_name<TResponse: Clone> {
+            in_flight: std::sync::Mutex<
+                std::collections::HashMap<
+                    Vec<u8>,
+                    Vec<tokio::sync::oneshot::Sender<Option<Result<TResponse, tonic::Status>>>>,
+                >,
+            >,
+        }
+
+        impl<TResponse: Clone> #single_flight_name<TResponse> {
+            fn new() -> Self {
+                Self {
+                    in_flight: std::sync::Mutex::new(std::collections::HashMap::new()),
+                }
+            }
+
+            fn join(
+                &self,
+                key: &[u8],
+            ) -> Option<tokio::sync::oneshot::Receiver<Option<Result<TResponse, tonic::Status>>>> {
+                let mut in_flight = self.in_flight.lock().unwrap();
+
+                if let Some(waiters) = in_flight.get_mut(key) {
+                    let (sender, receiver) = tokio::sync::oneshot::channel();
+                    waiters.push(sender);
+                    return Some(receiver);
+                }
+
+                in_flight.insert(key.to_vec(), Vec::new());
+                None
+            }
+
+            fn complete(&self, key: &[u8], result: Option<Result<TResponse, tonic::Status>>) {
+                let waiters = self.in_flight.lock().unwrap().remove(key);
+
+                if let Some(waiters) = waiters {
+                    for waiter in waiters {
+                        let result = match &result {
+                            Some(Ok(result)) => Some(Ok(result.clone())),
+                            Some(Err(status)) => Some(Err(tonic::Status::with_details_and_metadata(
+                                status.code(),
+                                status.message(),
+                                status.details().to_vec().into(),
+                                status.metadata().clone(),
+                            ))),
+                            None => None,
+                        };
+
+                        let _ = waiter.send(result);
+                    }
+                }
+            }
+        }
+
+        struct #guard_name<'s, TResponse: Clone> {
+            owner: &'s #single_flight_name<TResponse>,
+            key: Vec<u8>,
+            result: Option<Result<TResponse, tonic::Status>>,
+        }
+
+        impl<'s, TResponse: Clone> #guard_name<'s, TResponse> {
+            fn new(owner: &'s #single_flight_name<TResponse>, key: Vec<u8>) -> Self {
+                Self {
+                    owner,
+                    key,
+                    result: None,
+                }
+            }
+
+            fn set_result(&mut self, result: Result<TResponse, tonic::Status>) {
+                self.result = Some(result);
+            }
+        }
+
+        impl<'s, TResponse: Clone> Drop for #guard_name<'s, TResponse> {
+            fn drop(&mut self) {
+                self.owner.complete(&self.key, self.result.take());
+            }
+        }
+    }
+}
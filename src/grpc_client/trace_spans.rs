@@ -0,0 +1,76 @@
+use proc_macro2::{Ident, TokenStream};
+
+pub fn wrap_with_span(
+    struct_name: &Ident,
+    rpc_name: &str,
+    fn_body: TokenStream,
+    output_data_type: &TokenStream,
+) -> TokenStream {
+    let service_name = struct_name.to_string();
+
+    quote::quote! {
+        let span = tracing::info_span!(
+            #rpc_name,
+            service = #service_name,
+            method = #rpc_name,
+            attempt = tracing::field::Empty,
+            status = tracing::field::Empty,
+        );
+
+        let result: Result<#output_data_type, my_grpc_extensions::GrpcReadError> =
+            tracing::Instrument::instrument(
+                async {
+                    #fn_body
+                },
+                span.clone(),
+            )
+            .await;
+
+        match &result {
+            Ok(_) => {
+                span.record("status", "ok");
+            }
+            Err(err) => {
+                span.record("status", format!("{:?}", err).as_str());
+            }
+        }
+
+        result
+    }
+}
+
+// W3C traceparent of the current span is put into request metadata, so server side spans are linked to it
+pub fn generate_insert_traceparent() -> TokenStream {
+    quote::quote! {
+        {
+            use opentelemetry::trace::TraceContextExt;
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+            let context = tracing::Span::current().context();
+            let span_context = context.span().span_context().clone();
+
+            if span_context.is_valid() {
+                let traceparent = format!(
+                    "00-{}-{}-{:02x}",
+                    span_context.trace_id(),
+                    span_context.span_id(),
+                    span_context.trace_flags().to_u8()
+                );
+
+                if let Ok(value) = traceparent.parse() {
+                    request.metadata_mut().insert("traceparent", value);
+                }
+            }
+        }
+    }
+}
+
+pub fn generate_trace_reconnect(struct_name: &Ident) -> TokenStream {
+    let service_name = struct_name.to_string();
+    quote::quote!(tracing::info!(service = #service_name, "Grpc channel is connecting"););
+}
+
+pub fn generate_trace_ping_failure(struct_name: &Ident) -> TokenStream {
+    let service_name = struct_name.to_string();
+    quote::quote!(tracing::warn!(service = #service_name, "Grpc channel ping failed: {:?}", err););
+}
@@ -3,14 +3,21 @@ mod grpc_client;
 
 #[proc_macro_attribute]
 pub fn generate_grpc_client(attr: TokenStream, item: TokenStream) -> TokenStream {
-    #[cfg(feature = "with-telemetry")]
-    match crate::grpc_client::generate(attr, item, true) {
-        Ok(result) => result,
-        Err(err) => err.into_compile_error().into(),
-    }
+    let with_telemetry = cfg!(feature = "with-telemetry");
+    let with_fault_injection = cfg!(feature = "with-fault-injection");
+    let with_metrics = cfg!(feature = "with-metrics");
+    let with_tracing = cfg!(feature = "with-tracing");
+    let with_trace_propagation = cfg!(feature = "with-trace-propagation");
 
-    #[cfg(not(feature = "with-telemetry"))]
-    match crate::grpc_client::generate(attr, item, false) {
+    match crate::grpc_client::generate(
+        attr,
+        item,
+        with_telemetry,
+        with_fault_injection,
+        with_metrics,
+        with_tracing,
+        with_trace_propagation,
+    ) {
         Ok(result) => result,
         Err(err) => err.into_compile_error().into(),
     }
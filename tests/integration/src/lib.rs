@@ -0,0 +1,127 @@
+pub mod fixture_grpc {
+    tonic::include_proto!("fixture");
+}
+
+// Client of the fixture proto with its fake server. It goes to its own module, since the macro generates
+// module level items. Parameters are added to the common ones
+#[macro_export]
+macro_rules! fixture_client {
+    ($module:ident, $name:ident $(, $($params:tt)*)?) => {
+        mod $module {
+            #[my_grpc_client_macros::generate_grpc_client(
+                proto_file: "./proto/Fixture.proto",
+                crate_ns: "grpc_client_tests::fixture_grpc",
+                fake_server: true,
+                $($($params)*)?
+            )]
+            pub struct $name;
+        }
+    };
+}
+
+// Settings with urls which can be changed while the client runs, so a restarted server is found on its new port.
+// GrpcChannel asks for the url on every (re)connect, so the requests are counted
+#[derive(Default)]
+pub struct TestSettings {
+    urls: std::sync::Mutex<std::collections::HashMap<String, Vec<String>>>,
+    url_requests: std::sync::atomic::AtomicUsize,
+}
+
+impl TestSettings {
+    pub fn set_url(&self, name: &str, url: &str) {
+        self.set_urls(name, &[url]);
+    }
+
+    pub fn set_urls(&self, name: &str, urls: &[&str]) {
+        self.urls.lock().unwrap().insert(
+            name.to_string(),
+            urls.iter().map(|url| url.to_string()).collect(),
+        );
+    }
+
+    pub fn get_urls(&self, name: &str) -> Vec<String> {
+        self.urls
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn get_url_requests(&self) -> usize {
+        self.url_requests.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[async_trait::async_trait]
+impl my_grpc_extensions::GrpcClientSettings for TestSettings {
+    async fn get_grpc_url(&self, name: &'static str) -> String {
+        self.url_requests
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+        match self.get_urls(name).into_iter().next() {
+            Some(url) => url,
+            None => panic!("No url is set for {}", name),
+        }
+    }
+}
+
+// Handler of the fake server which answers after a delay and counts the calls
+pub fn get_handler(
+    calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    delay: std::time::Duration,
+) -> impl Fn(
+    fixture_grpc::GetRequest,
+) -> futures::future::BoxFuture<'static, Result<fixture_grpc::GetResponse, tonic::Status>>
+       + Send
+       + Sync
+       + 'static {
+    move |input| {
+        let calls = calls.clone();
+
+        Box::pin(async move {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+
+            Ok(fixture_grpc::GetResponse {
+                value: format!("value of {}", input.key),
+            })
+        })
+    }
+}
+
+pub fn get_request(key: &str) -> fixture_grpc::GetRequest {
+    fixture_grpc::GetRequest {
+        key: key.to_string(),
+        password: String::new(),
+    }
+}
+
+pub fn get_status(err: my_grpc_extensions::GrpcReadError) -> tonic::Status {
+    match err {
+        my_grpc_extensions::GrpcReadError::TonicStatus(status) => status,
+        err => panic!("Status error is expected. Got: {:?}", err),
+    }
+}
+
+// Client with a method which is less visible than the client, used by the compile fail tests
+pub mod visibility {
+    use my_grpc_client_macros::generate_grpc_client;
+
+    #[generate_grpc_client(
+        proto_file: "./proto/Fixture.proto",
+        crate_ns: "crate::fixture_grpc",
+        overrides: [
+            { fn_name: "Set", visibility: "pub(crate)" }
+        ]
+    )]
+    pub struct VisibilityClient;
+
+    // Code of the crate still calls the hidden method through the client
+    pub async fn set_value(
+        client: &VisibilityClient,
+        request: crate::fixture_grpc::SetRequest,
+    ) -> Result<(), my_grpc_extensions::GrpcReadError> {
+        client.set(request).await
+    }
+}
@@ -0,0 +1,33 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(client, ApiClient);
+
+use client::{ApiClient, ApiClientApi, ApiClientFakeServer};
+
+// Code under test takes the trait, so the client goes there as Arc<dyn Api>
+async fn get_value(api: Arc<dyn ApiClientApi>, key: &str) -> String {
+    api.get(get_request(key)).await.unwrap().value
+}
+
+#[tokio::test]
+async fn test_client_through_the_api() {
+    let server = ApiClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = Arc::new(ApiClient::new(Arc::new(server)));
+
+    assert_eq!(get_value(client.clone(), "a").await, "value of a");
+
+    let api: Arc<dyn ApiClientApi> = client.clone();
+    assert!(api.shutdown(Duration::from_secs(1)).await);
+    assert!(api.is_shut_down());
+    assert!(client.is_shut_down());
+}
@@ -0,0 +1,103 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(client, BlockingTestClient, blocking: true);
+
+use client::{
+    BlockingTestClient, BlockingTestClientBlocking, BlockingTestClientFakeServer,
+    BlockingTestClientFakeServerHandle,
+};
+
+// Fake server runs on a runtime of the test, since blocking client does not give its own one out
+fn start_server(
+    runtime: &tokio::runtime::Runtime,
+    calls: Arc<AtomicUsize>,
+) -> Arc<BlockingTestClientFakeServerHandle> {
+    let server = runtime
+        .block_on(
+            BlockingTestClientFakeServer::new()
+                .on_get(get_handler(calls, Duration::ZERO))
+                .start(),
+        )
+        .unwrap();
+
+    Arc::new(server)
+}
+
+fn get_multi_thread_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_calls_with_own_runtime() {
+    let runtime = get_multi_thread_runtime();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = start_server(&runtime, calls.clone());
+
+    let client = BlockingTestClientBlocking::new(|| BlockingTestClient::new(server)).unwrap();
+
+    let response = client.get(get_request("a")).unwrap();
+    assert_eq!(response.value, "value of a");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    assert!(client.shutdown(Duration::from_secs(1)));
+    assert!(client.is_shut_down());
+}
+
+#[test]
+fn test_calls_with_handle() {
+    let runtime = get_multi_thread_runtime();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server = start_server(&runtime, calls.clone());
+
+    let client = BlockingTestClientBlocking::with_handle(runtime.handle().clone(), || {
+        BlockingTestClient::new(server)
+    })
+    .unwrap();
+
+    let response = client.get(get_request("b")).unwrap();
+    assert_eq!(response.value, "value of b");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_current_thread_runtime_is_rejected() {
+    let runtime = get_multi_thread_runtime();
+    let server = start_server(&runtime, Arc::new(AtomicUsize::new(0)));
+
+    let current_thread = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let err = BlockingTestClientBlocking::with_handle(current_thread.handle().clone(), || {
+        BlockingTestClient::new(server)
+    })
+    .err()
+    .unwrap();
+
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[should_panic(expected = "is called from inside of an async context")]
+async fn test_call_from_async_context_panics() {
+    let server = BlockingTestClientFakeServer::new().start().await.unwrap();
+
+    let client = BlockingTestClientBlocking::with_handle(tokio::runtime::Handle::current(), || {
+        BlockingTestClient::new(Arc::new(server))
+    })
+    .unwrap();
+
+    let _ = client.get(get_request("a"));
+}
@@ -0,0 +1,67 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(client, BuiltClient);
+
+use client::{BuiltClient, BuiltClientFakeServer};
+
+#[tokio::test(start_paused = true)]
+async fn test_built_client_calls_the_server() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = BuiltClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = BuiltClient::builder(Arc::new(server))
+        .request_timeout(Duration::from_millis(750))
+        .ping_timeout(Duration::from_secs(1))
+        .ping_interval(Duration::from_secs(2))
+        .retries(0)
+        .metadata("x-app-name".parse().unwrap(), "tests".parse().unwrap())
+        .build()
+        .unwrap();
+
+    let response = client.get(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+}
+
+#[tokio::test]
+async fn test_invalid_timings_are_rejected() {
+    let server = BuiltClientFakeServer::new().start().await.unwrap();
+    let settings = Arc::new(server);
+
+    let err = BuiltClient::builder(settings.clone())
+        .request_timeout(Duration::ZERO)
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.contains("must not be zero"));
+
+    let err = BuiltClient::builder(settings.clone())
+        .ping_timeout(Duration::from_secs(5))
+        .ping_interval(Duration::from_secs(5))
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.contains("must be less than ping_interval"));
+
+    let err = BuiltClient::builder(settings.clone())
+        .request_timeout(Duration::MAX)
+        .build()
+        .err()
+        .unwrap();
+    assert!(err.contains("must be less than u64::MAX microseconds"));
+
+    assert!(BuiltClient::builder(settings)
+        .ping_timeout(Duration::from_secs(1))
+        .ping_interval(Duration::from_secs(5))
+        .build()
+        .is_ok());
+}
@@ -0,0 +1,72 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(
+    client,
+    CachedClient,
+    overrides: [
+        { fn_name: "Get", cache: { ttl: "300ms", max_entries: 10 } }
+    ]
+);
+
+use client::{CachedClient, CachedClientFakeServer};
+
+async fn start() -> (CachedClient, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = CachedClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    (CachedClient::new(Arc::new(server)), calls)
+}
+
+// Time is paused, so the entry expires exactly after the ttl
+#[tokio::test(start_paused = true)]
+async fn test_response_is_cached_until_ttl() {
+    let (client, calls) = start().await;
+
+    let first = client.get(get_request("a")).await.unwrap();
+    let second = client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(first.value, second.value);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    client.get(get_request("b")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    tokio::time::sleep(Duration::from_millis(299)).await;
+
+    client.get(get_request("a")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    tokio::time::sleep(Duration::from_millis(1)).await;
+
+    client.get(get_request("a")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_invalidate() {
+    let (client, calls) = start().await;
+
+    client.get(get_request("a")).await.unwrap();
+    client.invalidate_get();
+    client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    client.clear_cache();
+    client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
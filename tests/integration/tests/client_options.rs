@@ -0,0 +1,102 @@
+use std::{sync::Arc, time::Duration};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_request, get_status, TestSettings};
+use my_grpc_extensions::GrpcClientSettings;
+
+grpc_client_tests::fixture_client!(
+    client,
+    OptionsClient,
+    client_options: true,
+    retries: 0,
+    request_timeout: "2s",
+    ping_timeout: "100ms",
+    ping_interval: "200ms",
+);
+
+use client::{
+    OptionsClient, OptionsClientFakeServer, OptionsClientFakeServerHandle, OptionsClientOptions,
+    OptionsClientSettings,
+};
+
+// Options are read on every connect, so they are changed by the test between reconnects
+#[derive(Default)]
+struct OptionsSettings {
+    urls: TestSettings,
+    options: std::sync::Mutex<OptionsClientOptions>,
+}
+
+#[async_trait::async_trait]
+impl GrpcClientSettings for OptionsSettings {
+    async fn get_grpc_url(&self, name: &'static str) -> String {
+        self.urls.get_grpc_url(name).await
+    }
+}
+
+#[async_trait::async_trait]
+impl OptionsClientSettings for OptionsSettings {
+    async fn get_grpc_client_options(&self, _name: &'static str) -> OptionsClientOptions {
+        self.options.lock().unwrap().clone()
+    }
+}
+
+async fn start_server() -> OptionsClientFakeServerHandle {
+    OptionsClientFakeServer::new()
+        .on_get(|_| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            Ok(GetResponse {
+                value: "slow".to_string(),
+            })
+        })
+        .start()
+        .await
+        .unwrap()
+}
+
+// Time is paused, so the server delay, the timeouts and the pings go by the same clock
+#[tokio::test(start_paused = true)]
+async fn test_options_are_applied_on_reconnect() {
+    let settings = Arc::new(OptionsSettings::default());
+    *settings.options.lock().unwrap() = OptionsClientOptions {
+        request_timeout: Some(Duration::from_millis(500)),
+        ..Default::default()
+    };
+
+    let server = start_server().await;
+    settings
+        .urls
+        .set_url(OptionsClient::get_service_name(), server.get_url());
+
+    let client = OptionsClient::new(settings.clone());
+
+    let status = get_status(client.get(get_request("a")).await.unwrap_err());
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // Options are not read again until the channel reconnects
+    *settings.options.lock().unwrap() = OptionsClientOptions::default();
+
+    let status = get_status(client.get(get_request("a")).await.unwrap_err());
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // Server comes back on another port, so the channel reconnects and reads the options
+    drop(server);
+    let server = start_server().await;
+    settings
+        .urls
+        .set_url(OptionsClient::get_service_name(), server.get_url());
+
+    let started = tokio::time::Instant::now();
+
+    loop {
+        if let Ok(response) = client.get(get_request("a")).await {
+            assert_eq!(response.value, "slow");
+            break;
+        }
+
+        if started.elapsed() > Duration::from_secs(10) {
+            panic!("Client does not reconnect with the new options");
+        }
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
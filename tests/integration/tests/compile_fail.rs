@@ -0,0 +1,6 @@
+// Expected compiler output is kept next to each case. Run with TRYBUILD=overwrite to refresh it
+#[test]
+fn test_compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,82 @@
+use std::{sync::Arc, time::Duration};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_request, TestSettings};
+
+grpc_client_tests::fixture_client!(
+    client,
+    FailoverClient,
+    retries: 0,
+    request_timeout: "1s",
+    ping_timeout: "100ms",
+    ping_interval: "200ms",
+    failover: { secondary_service_name: "FailoverClientSecondary", failback_after: "1s" },
+);
+
+use client::{FailoverClient, FailoverClientFakeServer, FailoverClientFakeServerHandle};
+
+const PRIMARY: &str = "FailoverClient";
+const SECONDARY: &str = "FailoverClientSecondary";
+
+async fn start_server(value: &'static str) -> FailoverClientFakeServerHandle {
+    FailoverClientFakeServer::new()
+        .on_get(move |_| async move {
+            Ok(GetResponse {
+                value: value.to_string(),
+            })
+        })
+        .start()
+        .await
+        .unwrap()
+}
+
+// Calls fail while the client finds out that the endpoint is gone, so they are repeated until the expected one answers
+async fn wait_for_endpoint(client: &FailoverClient, value: &str, timeout: Duration) {
+    let started = tokio::time::Instant::now();
+
+    loop {
+        if let Ok(response) = client.get(get_request("a")).await {
+            if response.value == value {
+                return;
+            }
+        }
+
+        if started.elapsed() > timeout {
+            panic!("Calls do not go to {} in {:?}", value, timeout);
+        }
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+// Time is paused, so pings, failback_after and the timeouts of the waits go by the same clock
+#[tokio::test(start_paused = true)]
+async fn test_primary_secondary_primary_cycle() {
+    let settings = Arc::new(TestSettings::default());
+
+    let primary = start_server("primary").await;
+    let secondary = start_server("secondary").await;
+
+    settings.set_url(PRIMARY, primary.get_url());
+    settings.set_url(SECONDARY, secondary.get_url());
+
+    let client = FailoverClient::new(settings.clone());
+
+    wait_for_endpoint(&client, "primary", Duration::from_secs(1)).await;
+    assert_eq!(client.get_active_service_name(), PRIMARY);
+
+    drop(primary);
+
+    wait_for_endpoint(&client, "secondary", Duration::from_secs(3)).await;
+    assert_eq!(client.get_active_service_name(), SECONDARY);
+
+    // Primary comes back on another port. Nothing calls it, so only the background probe can find it
+    let primary = start_server("primary").await;
+    settings.set_url(PRIMARY, primary.get_url());
+
+    // It has to answer pings for failback_after before calls go back to it
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(client.get_active_service_name(), SECONDARY);
+
+    wait_for_endpoint(&client, "primary", Duration::from_secs(5)).await;
+    assert_eq!(client.get_active_service_name(), PRIMARY);
+}
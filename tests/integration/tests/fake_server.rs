@@ -0,0 +1,41 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{fixture_grpc::SetRequest, get_handler, get_request, get_status};
+
+grpc_client_tests::fixture_client!(
+    client,
+    FakeServerClient,
+    retries: 0,
+);
+
+use client::{FakeServerClient, FakeServerClientFakeServer};
+
+#[tokio::test]
+async fn test_calls_go_to_handlers() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = FakeServerClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = FakeServerClient::new(Arc::new(server));
+
+    let response = client.get(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+
+    let err = client
+        .set(SetRequest {
+            key: "a".to_string(),
+            value: "b".to_string(),
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(get_status(err).code(), tonic::Code::Unimplemented);
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "fault-injection")]
+
+use std::sync::{atomic::AtomicUsize, Arc};
+
+use grpc_client_tests::{get_handler, get_request, get_status, TestSettings};
+
+grpc_client_tests::fixture_client!(client, FaultyClient, retries: 0);
+
+use client::{FaultyClient, FaultyClientFakeServer, FaultyClientFault};
+
+#[test]
+fn test_fault_probability_out_of_range_is_an_error() {
+    assert!(FaultyClientFault::new(1.5).is_err());
+    assert!(FaultyClientFault::new(-0.1).is_err());
+    assert!(FaultyClientFault::new(1.0).is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_status_code_fault_fails_the_call() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = FaultyClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), std::time::Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = FaultyClient::new(Arc::new(server));
+
+    client.get_fault_injector().set_fault(
+        "Get",
+        FaultyClientFault::new(1.0)
+            .unwrap()
+            .with_status_code(tonic::Code::Internal),
+    );
+
+    let err = client.get(get_request("a")).await.unwrap_err();
+    assert_eq!(get_status(err).code(), tonic::Code::Internal);
+
+    client.get_fault_injector().clear_all();
+    client.get(get_request("a")).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_disconnect_fault_connects_again() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = FaultyClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), std::time::Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let settings = Arc::new(TestSettings::default());
+    settings.set_url(FaultyClient::get_service_name(), server.get_url());
+
+    let client = FaultyClient::new(settings.clone());
+
+    client.get(get_request("a")).await.unwrap();
+    let url_requests = settings.get_url_requests();
+
+    client.get_fault_injector().set_fault(
+        "Get",
+        FaultyClientFault::new(1.0).unwrap().with_disconnect(),
+    );
+
+    let err = client.get(get_request("a")).await.unwrap_err();
+    assert_eq!(get_status(err).code(), tonic::Code::Unavailable);
+
+    client.get_fault_injector().clear_fault("Get");
+    client.get(get_request("a")).await.unwrap();
+
+    // The new channel asks the settings for the url again
+    assert!(settings.get_url_requests() > url_requests);
+}
@@ -0,0 +1,105 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_handler, get_request};
+
+grpc_client_tests::fixture_client!(
+    client,
+    HedgedClient,
+    retries: 0,
+    overrides: [
+        { fn_name: "Get", hedge: { after: "50ms", max_extra: 1 } }
+    ]
+);
+
+use client::{HedgedClient, HedgedClientFakeServer};
+
+// Time is paused, so the delays of the server and the hedge timer are exact
+#[tokio::test(start_paused = true)]
+async fn test_hedge_answers_before_slow_first_attempt() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server_calls = calls.clone();
+
+    let server = HedgedClientFakeServer::new()
+        .on_get(move |_| {
+            let call = server_calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+            async move {
+                if call == 1 {
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                }
+
+                Ok(GetResponse {
+                    value: format!("call {}", call),
+                })
+            }
+        })
+        .start()
+        .await
+        .unwrap();
+
+    let client = HedgedClient::new(Arc::new(server));
+
+    let started = tokio::time::Instant::now();
+    let response = client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(response.value, "call 2");
+    assert!(started.elapsed() >= Duration::from_millis(50));
+    assert!(started.elapsed() < Duration::from_secs(3));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_fast_call_is_not_hedged() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = HedgedClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = HedgedClient::new(Arc::new(server));
+
+    client.get(get_request("a")).await.unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_failed_attempt_starts_hedge_without_waiting_for_the_timer() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server_calls = calls.clone();
+
+    let server = HedgedClientFakeServer::new()
+        .on_get(move |_| {
+            let call = server_calls.fetch_add(1, Ordering::SeqCst) + 1;
+
+            async move {
+                if call == 1 {
+                    return Err(tonic::Status::invalid_argument("First attempt fails"));
+                }
+
+                Ok(GetResponse {
+                    value: format!("call {}", call),
+                })
+            }
+        })
+        .start()
+        .await
+        .unwrap();
+
+    let client = HedgedClient::new(Arc::new(server));
+
+    let started = tokio::time::Instant::now();
+    let response = client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(response.value, "call 2");
+    assert!(started.elapsed() < Duration::from_millis(50));
+}
@@ -0,0 +1,123 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request, get_status};
+
+grpc_client_tests::fixture_client!(
+    concurrency,
+    ConcurrencyClient,
+    retries: 0,
+    max_concurrency: 1,
+    on_limit: "fail_fast",
+);
+
+grpc_client_tests::fixture_client!(
+    rate,
+    RateClient,
+    retries: 0,
+    rate_limit: { per_sec: 1, burst: 1 },
+    on_limit: "fail_fast",
+);
+
+grpc_client_tests::fixture_client!(
+    waiting,
+    WaitingClient,
+    retries: 0,
+    rate_limit: { per_sec: 1, burst: 1 },
+);
+
+use concurrency::{ConcurrencyClient, ConcurrencyClientError, ConcurrencyClientFakeServer};
+use rate::{RateClient, RateClientError, RateClientFakeServer};
+use waiting::{WaitingClient, WaitingClientFakeServer};
+
+// Time is paused, so the slow call is still in flight when the limited one is made
+#[tokio::test(start_paused = true)]
+async fn test_concurrency_limit_fails_fast() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = ConcurrencyClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::from_millis(300)))
+        .start()
+        .await
+        .unwrap();
+
+    let client = Arc::new(ConcurrencyClient::new(Arc::new(server)));
+
+    let slow_call = {
+        let client = client.clone();
+        tokio::spawn(async move { client.get(get_request("slow")).await })
+    };
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let status = get_status(client.get(get_request("limited")).await.unwrap_err());
+
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+    assert_eq!(
+        ConcurrencyClientError::from_status(&status),
+        Some(ConcurrencyClientError::ConcurrencyLimitExceeded)
+    );
+
+    slow_call.await.unwrap().unwrap();
+
+    // Permit is released once the call is finished
+    client.get(get_request("after")).await.unwrap();
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_fails_fast() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = RateClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = RateClient::new(Arc::new(server));
+
+    client.get(get_request("a")).await.unwrap();
+
+    let status = get_status(client.get(get_request("b")).await.unwrap_err());
+
+    assert_eq!(
+        RateClientError::from_status(&status),
+        Some(RateClientError::RateLimitExceeded)
+    );
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // One token is added per second
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    client.get(get_request("c")).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_rate_limit_waits_for_a_token() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = WaitingClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = WaitingClient::new(Arc::new(server));
+
+    let started = tokio::time::Instant::now();
+
+    client.get(get_request("a")).await.unwrap();
+    client.get(get_request("b")).await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_server_statuses_are_not_client_errors() {
+    let status = tonic::Status::resource_exhausted("Server is busy");
+
+    assert_eq!(RateClientError::from_status(&status), None);
+}
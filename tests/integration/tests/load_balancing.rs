@@ -0,0 +1,144 @@
+use std::{sync::Arc, time::Duration};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_request, get_status, TestSettings};
+
+grpc_client_tests::fixture_client!(
+    round_robin,
+    RoundRobinClient,
+    retries: 0,
+    ping_timeout: "100ms",
+    ping_interval: "200ms",
+    load_balancing: "round_robin",
+);
+
+grpc_client_tests::fixture_client!(
+    least_outstanding,
+    LeastOutstandingClient,
+    retries: 0,
+    load_balancing: "least_outstanding",
+);
+
+use least_outstanding::{LeastOutstandingClient, LeastOutstandingClientEndpointsSettings};
+use round_robin::{
+    RoundRobinClient, RoundRobinClientEndpointsSettings, RoundRobinClientError,
+    RoundRobinClientFakeServer, RoundRobinClientFakeServerHandle,
+};
+
+#[async_trait::async_trait]
+impl RoundRobinClientEndpointsSettings for TestSettings {
+    async fn get_grpc_urls(&self, name: &'static str) -> Vec<String> {
+        self.get_urls(name)
+    }
+}
+
+#[async_trait::async_trait]
+impl LeastOutstandingClientEndpointsSettings for TestSettings {
+    async fn get_grpc_urls(&self, name: &'static str) -> Vec<String> {
+        self.get_urls(name)
+    }
+}
+
+// Servers of both clients are the same, so the fake server of one client serves the other one as well
+async fn start_server(value: &'static str, delay: Duration) -> RoundRobinClientFakeServerHandle {
+    RoundRobinClientFakeServer::new()
+        .on_get(move |_| async move {
+            tokio::time::sleep(delay).await;
+
+            Ok(GetResponse {
+                value: value.to_string(),
+            })
+        })
+        .start()
+        .await
+        .unwrap()
+}
+
+async fn get_values(client: &RoundRobinClient, calls: usize) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for _ in 0..calls {
+        result.push(client.get(get_request("a")).await.unwrap().value);
+    }
+
+    result
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_round_robin_alternates_endpoints() {
+    let first = start_server("first", Duration::ZERO).await;
+    let second = start_server("second", Duration::ZERO).await;
+
+    let settings = Arc::new(TestSettings::default());
+    settings.set_urls(
+        RoundRobinClient::get_service_name(),
+        &[first.get_url(), second.get_url()],
+    );
+
+    let client = RoundRobinClient::new(settings);
+
+    assert_eq!(
+        get_values(&client, 4).await,
+        vec!["first", "second", "first", "second"]
+    );
+}
+
+// Time is paused, so pings of the dropped endpoint fail within ping_timeout of the virtual clock
+#[tokio::test(start_paused = true)]
+async fn test_unhealthy_endpoint_is_ejected() {
+    let first = start_server("first", Duration::ZERO).await;
+    let second = start_server("second", Duration::ZERO).await;
+
+    let settings = Arc::new(TestSettings::default());
+    settings.set_urls(
+        RoundRobinClient::get_service_name(),
+        &[first.get_url(), second.get_url()],
+    );
+
+    let client = RoundRobinClient::new(settings);
+
+    assert_eq!(get_values(&client, 2).await, vec!["first", "second"]);
+
+    drop(first);
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    assert_eq!(get_values(&client, 4).await, vec!["second"; 4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_least_outstanding_skips_busy_endpoint() {
+    let slow = start_server("slow", Duration::from_secs(10)).await;
+    let fast = start_server("fast", Duration::ZERO).await;
+
+    let settings = Arc::new(TestSettings::default());
+    settings.set_urls(
+        LeastOutstandingClient::get_service_name(),
+        &[slow.get_url(), fast.get_url()],
+    );
+
+    let client = Arc::new(LeastOutstandingClient::new(settings));
+
+    // Both endpoints are idle, so the first call goes to the first one and keeps it busy
+    let busy_client = client.clone();
+    let busy_call = tokio::spawn(async move { busy_client.get(get_request("a")).await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    for _ in 0..3 {
+        let response = client.get(get_request("a")).await.unwrap();
+        assert_eq!(response.value, "fast");
+    }
+
+    assert_eq!(busy_call.await.unwrap().unwrap().value, "slow");
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_no_urls_is_an_error() {
+    let client = RoundRobinClient::new(Arc::new(TestSettings::default()));
+
+    let status = get_status(client.get(get_request("a")).await.unwrap_err());
+
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert_eq!(
+        RoundRobinClientError::from_status(&status),
+        Some(RoundRobinClientError::NoEndpoints)
+    );
+}
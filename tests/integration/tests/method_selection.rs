@@ -0,0 +1,59 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(included, IncludedClient, include: ["Get"]);
+grpc_client_tests::fixture_client!(excluded, ExcludedClient, exclude: ["Set"]);
+grpc_client_tests::fixture_client!(
+    renamed,
+    RenamedClient,
+    overrides: [
+        { fn_name: "Get", rename_to: "get_value" }
+    ]
+);
+
+// Fake server implements all the rpcs whatever methods the client has
+#[tokio::test]
+async fn test_included_method() {
+    let server = included::IncludedClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = included::IncludedClient::new(Arc::new(server));
+
+    let response = client.get(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+}
+
+#[tokio::test]
+async fn test_methods_left_after_exclude() {
+    let server = excluded::ExcludedClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = excluded::ExcludedClient::new(Arc::new(server));
+
+    let response = client.get(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+}
+
+#[tokio::test]
+async fn test_renamed_method() {
+    let server = renamed::RenamedClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = renamed::RenamedClient::new(Arc::new(server));
+
+    let response = client.get_value(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "metrics")]
+
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+grpc_client_tests::fixture_client!(client, MetricsClient, retries: 0);
+
+// Same name in another module registers the same metrics
+mod other {
+    grpc_client_tests::fixture_client!(client, MetricsClient, retries: 0);
+}
+
+use client::{MetricsClient, MetricsClientFakeServer};
+
+fn get_label<'m>(metric: &'m prometheus::proto::Metric, name: &str) -> Option<&'m str> {
+    metric
+        .get_label()
+        .iter()
+        .find(|label| label.get_name() == name)
+        .map(|label| label.get_value())
+}
+
+// Sum of the counters or of the histogram sample counts of the client for the Get rpc
+fn get_value(family_name: &str) -> u64 {
+    prometheus::gather()
+        .iter()
+        .filter(|family| family.get_name() == family_name)
+        .flat_map(|family| family.get_metric())
+        .filter(|metric| get_label(metric, "client") == Some("MetricsClient"))
+        .filter(|metric| get_label(metric, "rpc") == Some("Get"))
+        .map(|metric| {
+            if metric.has_histogram() {
+                metric.get_histogram().get_sample_count()
+            } else {
+                metric.get_counter().get_value() as u64
+            }
+        })
+        .sum()
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_calls_are_recorded() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = MetricsClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = MetricsClient::new(Arc::new(server));
+
+    client.get(get_request("a")).await.unwrap();
+    client.get(get_request("b")).await.unwrap();
+
+    assert_eq!(get_value("grpc_client_request_duration_seconds"), 2);
+    assert_eq!(get_value("grpc_client_responses_total"), 2);
+
+    // Client with the same name does not panic on the registered metrics. The first client stays exported
+    let other_server = other::client::MetricsClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let other_client = other::client::MetricsClient::new(Arc::new(other_server));
+    other_client.get(get_request("c")).await.unwrap();
+
+    assert_eq!(get_value("grpc_client_request_duration_seconds"), 2);
+}
@@ -0,0 +1,74 @@
+use std::{sync::Arc, time::Duration};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_request, get_status};
+
+grpc_client_tests::fixture_client!(client, MockedClient, mock: true);
+
+use client::{MockedClientApi, MockedClientMock};
+
+// Code under test takes the trait, so the mock goes there instead of the client
+async fn get_value(api: Arc<dyn MockedClientApi>, key: &str) -> String {
+    match api.get(get_request(key)).await {
+        Ok(response) => response.value,
+        Err(err) => get_status(err).message().to_string(),
+    }
+}
+
+fn get_response(value: &str) -> GetResponse {
+    GetResponse {
+        value: value.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_returns_in_order_and_records_calls() {
+    let mock = Arc::new(MockedClientMock::new());
+
+    let err = my_grpc_extensions::GrpcReadError::TonicStatus(tonic::Status::not_found("second"));
+
+    mock.get_returns(Ok(get_response("first")))
+        .get_returns(Err(err));
+
+    assert_eq!(get_value(mock.clone(), "a").await, "first");
+    assert_eq!(get_value(mock.clone(), "b").await, "second");
+
+    let keys: Vec<String> = mock.get_calls().into_iter().map(|call| call.key).collect();
+    assert_eq!(keys, vec!["a".to_string(), "b".to_string()]);
+    assert!(mock.set_calls().is_empty());
+}
+
+#[tokio::test]
+async fn test_returns_when_matches_the_input() {
+    let mock = Arc::new(MockedClientMock::default());
+
+    mock.get_returns_when(|input| input.key == "b", Ok(get_response("of b")))
+        .get_returns(Ok(get_response("any")));
+
+    assert_eq!(get_value(mock.clone(), "a").await, "any");
+    assert_eq!(get_value(mock.clone(), "b").await, "of b");
+}
+
+// Panic of a call without a queued result does not break the mock for the rest of the test
+#[tokio::test]
+async fn test_missing_result_panics() {
+    let mock = Arc::new(MockedClientMock::new());
+
+    let api = mock.clone();
+    let err = tokio::spawn(async move { get_value(api, "a").await })
+        .await
+        .err()
+        .unwrap();
+    assert!(err.is_panic());
+
+    mock.get_returns(Ok(get_response("after panic")));
+    assert_eq!(get_value(mock.clone(), "a").await, "after panic");
+}
+
+#[tokio::test]
+async fn test_shutdown_of_the_mock() {
+    let mock = MockedClientMock::new();
+
+    assert!(!mock.is_shut_down());
+    assert!(mock.shutdown(Duration::from_secs(1)).await);
+    assert!(mock.is_shut_down());
+}
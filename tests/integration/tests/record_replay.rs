@@ -0,0 +1,100 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use grpc_client_tests::{fixture_grpc::GetResponse, get_request, get_status, TestSettings};
+
+grpc_client_tests::fixture_client!(
+    client,
+    RecordedClient,
+    record_replay: true,
+    retries: 0,
+);
+
+use client::{RecordedClient, RecordedClientFakeServer};
+
+fn get_file_name(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("grpc-client-{}-{}.txt", name, std::process::id()))
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_replay_without_the_server() {
+    let file_name = get_file_name("record");
+
+    let _ = std::fs::remove_file(&file_name);
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server_calls = calls.clone();
+
+    let server = RecordedClientFakeServer::new()
+        .on_get(move |input| {
+            server_calls.fetch_add(1, Ordering::SeqCst);
+
+            async move {
+                if input.key == "denied" {
+                    return Err(tonic::Status::permission_denied("No access to denied"));
+                }
+
+                Ok(GetResponse {
+                    value: format!("value of {}", input.key),
+                })
+            }
+        })
+        .start()
+        .await
+        .unwrap();
+
+    // Client keeps the url only, so the server can be stopped before the replay
+    let settings = Arc::new(TestSettings::default());
+    settings.set_url(RecordedClient::get_service_name(), server.get_url());
+
+    let client = RecordedClient::new(settings);
+
+    client.start_recording(file_name.as_str()).unwrap();
+    client.get(get_request("a")).await.unwrap();
+    client.get(get_request("denied")).await.unwrap_err();
+    client.stop_record_replay();
+
+    drop(server);
+
+    client.start_replay(file_name.as_str()).unwrap();
+
+    let response = client.get(get_request("a")).await.unwrap();
+    assert_eq!(response.value, "value of a");
+
+    let status = get_status(client.get(get_request("denied")).await.unwrap_err());
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    assert_eq!(status.message(), "No access to denied");
+
+    let status = get_status(client.get(get_request("b")).await.unwrap_err());
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    assert_eq!(client.get_unrecorded_requests().len(), 1);
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    std::fs::remove_file(&file_name).unwrap();
+}
+
+#[tokio::test]
+async fn test_replay_file_errors() {
+    let client = RecordedClient::new(Arc::new(TestSettings::default()));
+
+    let err = client
+        .start_replay(get_file_name("missing").as_str())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+    // Error without a status code is not a valid call
+    let file_name = get_file_name("invalid");
+    std::fs::write(&file_name, "Get\t0a0161\tERR\t00\t10\n").unwrap();
+
+    let err = client.start_replay(file_name.as_str()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    std::fs::remove_file(&file_name).unwrap();
+}
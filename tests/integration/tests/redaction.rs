@@ -0,0 +1,78 @@
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use grpc_client_tests::fixture_grpc::{GetRequest, GetResponse};
+
+grpc_client_tests::fixture_client!(
+    client,
+    LoggedClient,
+    log_payloads: "full",
+    redact: ["password"],
+);
+
+use client::{LoggedClient, LoggedClientFakeServer};
+
+const SECRET: &str = "s3cr3t, key: visible";
+
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Logs {
+    fn get(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+// Test runs on a current thread runtime, so the subscriber set for the thread gets the logs of the call
+#[tokio::test]
+async fn test_redacted_field_is_not_logged() {
+    let logs = Logs::default();
+
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .finish();
+
+    let _subscriber = tracing::subscriber::set_default(subscriber);
+
+    let server = LoggedClientFakeServer::new()
+        .on_get(|input| async move {
+            Ok(GetResponse {
+                value: format!("value of {}", input.key),
+            })
+        })
+        .start()
+        .await
+        .unwrap();
+
+    let client = LoggedClient::new(Arc::new(server));
+
+    client
+        .get(GetRequest {
+            key: "user".to_string(),
+            password: SECRET.to_string(),
+        })
+        .await
+        .unwrap();
+
+    let logs = logs.get();
+
+    assert!(logs.contains("Grpc call succeeded"), "{}", logs);
+    assert!(logs.contains("value of user"), "{}", logs);
+    assert!(logs.contains("***"), "{}", logs);
+    assert!(!logs.contains("s3cr3t"), "{}", logs);
+}
@@ -0,0 +1,104 @@
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request, get_status};
+
+grpc_client_tests::fixture_client!(
+    client,
+    StoppedClient,
+    mock: true,
+    retries: 0,
+    ping_timeout: "100ms",
+    ping_interval: "200ms",
+);
+
+use client::{
+    StoppedClient, StoppedClientApi, StoppedClientError, StoppedClientFakeServer, StoppedClientMock,
+};
+
+async fn start(delay: Duration) -> Arc<StoppedClient> {
+    let server = StoppedClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), delay))
+        .start()
+        .await
+        .unwrap();
+
+    Arc::new(StoppedClient::new(Arc::new(server)))
+}
+
+// Time is paused, so the server delays and the drain timeouts go by the same clock
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_drains_calls_in_flight() {
+    let client = start(Duration::from_millis(300)).await;
+
+    let call_in_flight = {
+        let client = client.clone();
+        tokio::spawn(async move { client.get(get_request("a")).await })
+    };
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(client.shutdown(Duration::from_secs(2)).await);
+    assert!(client.is_shut_down());
+
+    assert_eq!(call_in_flight.await.unwrap().unwrap().value, "value of a");
+
+    let status = get_status(client.get(get_request("b")).await.unwrap_err());
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert_eq!(
+        StoppedClientError::from_status(&status),
+        Some(StoppedClientError::ShutDown)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_reports_calls_left_in_flight() {
+    let client = start(Duration::from_millis(500)).await;
+
+    let call_in_flight = {
+        let client = client.clone();
+        tokio::spawn(async move { client.get(get_request("a")).await })
+    };
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert!(!client.shutdown(Duration::from_millis(10)).await);
+
+    // Call keeps its channel after the client drops it
+    assert!(call_in_flight.await.unwrap().is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_shutdown_through_api() {
+    let client = start(Duration::ZERO).await;
+    let api: Arc<dyn StoppedClientApi> = client.clone();
+
+    assert!(api.shutdown(Duration::from_secs(1)).await);
+    assert!(client.is_shut_down());
+
+    let mock = Arc::new(StoppedClientMock::new());
+    let api: Arc<dyn StoppedClientApi> = mock.clone();
+
+    assert!(!api.is_shut_down());
+    assert!(api.shutdown(Duration::from_secs(1)).await);
+    assert!(mock.is_shut_down());
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_pings_stop_after_shutdown() {
+    let server = Arc::new(StoppedClientFakeServer::new().start().await.unwrap());
+    let client = StoppedClient::new(server.clone());
+
+    // Channel is connected by the first call, then the ping loop starts
+    let _ = client.get(get_request("a")).await;
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(server.get_pings() > 0);
+
+    assert!(client.shutdown(Duration::from_secs(1)).await);
+    let pings = server.get_pings();
+
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    assert_eq!(server.get_pings(), pings);
+}
@@ -0,0 +1,91 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request, get_status};
+
+grpc_client_tests::fixture_client!(
+    client,
+    SingleFlightClient,
+    overrides: [
+        { fn_name: "Get", single_flight: true, retries: 0 }
+    ]
+);
+
+use client::{SingleFlightClient, SingleFlightClientFakeServer};
+
+// Time is paused, so all the callers come while the first call waits for the slow server
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_callers_share_the_response() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = SingleFlightClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::from_millis(200)))
+        .start()
+        .await
+        .unwrap();
+
+    let client = SingleFlightClient::new(Arc::new(server));
+
+    let results = futures::future::join_all((0..5).map(|_| client.get(get_request("a")))).await;
+
+    for result in results {
+        assert_eq!(result.unwrap().value, "value of a");
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_concurrent_callers_share_the_error() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server_calls = calls.clone();
+
+    let server = SingleFlightClientFakeServer::new()
+        .on_get(move |_| {
+            server_calls.fetch_add(1, Ordering::SeqCst);
+
+            async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Err(tonic::Status::permission_denied("No access"))
+            }
+        })
+        .start()
+        .await
+        .unwrap();
+
+    let client = SingleFlightClient::new(Arc::new(server));
+
+    let results = futures::future::join_all((0..5).map(|_| client.get(get_request("a")))).await;
+
+    for result in results {
+        let status = get_status(result.unwrap_err());
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(status.message(), "No access");
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_different_requests_are_not_shared() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = SingleFlightClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::from_millis(100)))
+        .start()
+        .await
+        .unwrap();
+
+    let client = SingleFlightClient::new(Arc::new(server));
+
+    let (a, b) = tokio::join!(client.get(get_request("a")), client.get(get_request("b")));
+
+    assert_eq!(a.unwrap().value, "value of a");
+    assert_eq!(b.unwrap().value, "value of b");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "telemetry")]
+
+use std::{
+    sync::{atomic::AtomicUsize, Arc},
+    time::Duration,
+};
+
+use grpc_client_tests::{get_handler, get_request};
+
+// No telemetry attribute: the feature is on, but the methods keep the plain signatures
+grpc_client_tests::fixture_client!(client, PlainClient);
+
+grpc_client_tests::fixture_client!(ctx_client, CtxClient, telemetry: true);
+
+use client::{PlainClient, PlainClientFakeServer};
+use ctx_client::{CtxClient, CtxClientFakeServer};
+
+#[tokio::test(start_paused = true)]
+async fn test_methods_without_attribute_keep_plain_signatures() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = PlainClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = PlainClient::new(Arc::new(server));
+
+    client.get(get_request("a")).await.unwrap();
+
+    let ctx = my_telemetry::MyTelemetryContext::new();
+    client.get_with_ctx(get_request("a"), &ctx).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_telemetry_attribute_adds_ctx_param() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = CtxClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = CtxClient::new(Arc::new(server));
+
+    let ctx = my_telemetry::MyTelemetryContext::new();
+    client.get(get_request("a"), &ctx).await.unwrap();
+}
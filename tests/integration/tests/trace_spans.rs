@@ -0,0 +1,119 @@
+#![cfg(feature = "trace-spans")]
+
+use std::{
+    collections::HashMap,
+    sync::{atomic::AtomicUsize, Arc, Mutex},
+    time::Duration,
+};
+
+use grpc_client_tests::{fixture_grpc::SetRequest, get_handler, get_request};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    Layer,
+};
+
+grpc_client_tests::fixture_client!(client, TracedClient);
+
+use client::{TracedClient, TracedClientFakeServer};
+
+// Fields of every span, including the ones recorded after the span is created.
+// Ids of closed spans can be reused, so the latest span with the id gets the record
+#[derive(Clone, Default)]
+struct SpanRecorder {
+    spans: Arc<Mutex<Vec<(tracing::span::Id, &'static str, HashMap<String, String>)>>>,
+}
+
+impl SpanRecorder {
+    fn get_spans(&self, name: &str) -> Vec<HashMap<String, String>> {
+        self.spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, span_name, _)| *span_name == name)
+            .map(|(_, _, fields)| fields.clone())
+            .collect()
+    }
+}
+
+struct FieldVisitor<'s>(&'s mut HashMap<String, String>);
+
+impl tracing::field::Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: tracing::Subscriber> Layer<S> for SpanRecorder {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        _ctx: Context<'_, S>,
+    ) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+
+        self.spans
+            .lock()
+            .unwrap()
+            .push((id.clone(), attrs.metadata().name(), fields));
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        _ctx: Context<'_, S>,
+    ) {
+        let mut spans = self.spans.lock().unwrap();
+
+        if let Some((_, _, fields)) = spans.iter_mut().rev().find(|(span_id, _, _)| span_id == id) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+}
+
+// Test runtime has one thread, so the subscriber set for the test sees the spans of the client
+#[tokio::test]
+async fn test_calls_run_inside_spans() {
+    let recorder = SpanRecorder::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+    let server = TracedClientFakeServer::new()
+        .on_get(get_handler(Arc::new(AtomicUsize::new(0)), Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    let client = TracedClient::new(Arc::new(server));
+
+    client.get(get_request("a")).await.unwrap();
+
+    let err = client
+        .set(SetRequest {
+            key: "a".to_string(),
+            value: "b".to_string(),
+        })
+        .await
+        .err()
+        .unwrap();
+    assert!(format!("{:?}", err).contains("Unimplemented"));
+
+    let spans = recorder.get_spans("Get");
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0]["service"], "TracedClient");
+    assert_eq!(spans[0]["method"], "Get");
+    assert_eq!(spans[0]["attempt"], "1");
+    assert_eq!(spans[0]["status"], "ok");
+
+    let spans = recorder.get_spans("Set");
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0]["method"], "Set");
+    assert!(spans[0]["status"].contains("Unimplemented"));
+}
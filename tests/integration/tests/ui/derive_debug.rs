@@ -0,0 +1,9 @@
+// Generated fields do not implement Debug, so the derive is reported on the attribute
+#[my_grpc_client_macros::generate_grpc_client(
+    proto_file: "./proto/Fixture.proto",
+    crate_ns: "grpc_client_tests::fixture_grpc",
+)]
+#[derive(Debug)]
+pub struct DebugClient;
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Set renamed to get clashes with the method of Get
+#[my_grpc_client_macros::generate_grpc_client(
+    proto_file: "./proto/Fixture.proto",
+    crate_ns: "grpc_client_tests::fixture_grpc",
+    overrides: [
+        { fn_name: "Set", rename_to: "get" }
+    ]
+)]
+pub struct DuplicateClient;
+
+fn main() {}
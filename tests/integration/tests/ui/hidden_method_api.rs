@@ -0,0 +1,10 @@
+use grpc_client_tests::{fixture_grpc::SetRequest, visibility::VisibilityClientApi};
+
+// Set is pub(crate) in the client, so it is left out of the Api trait
+async fn set(api: &dyn VisibilityClientApi) {
+    let _ = api.set(SetRequest::default()).await;
+}
+
+fn main() {
+    let _ = set;
+}
@@ -0,0 +1,12 @@
+// Excluded method is not generated, so its override is a mistake
+#[my_grpc_client_macros::generate_grpc_client(
+    proto_file: "./proto/Fixture.proto",
+    crate_ns: "grpc_client_tests::fixture_grpc",
+    exclude: ["Set"],
+    overrides: [
+        { fn_name: "Set", rename_to: "set_value" }
+    ]
+)]
+pub struct ExcludedClient;
+
+fn main() {}
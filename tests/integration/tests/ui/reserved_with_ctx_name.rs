@@ -0,0 +1,11 @@
+// Set renamed to get_with_ctx clashes with the function which is generated for Get
+#[my_grpc_client_macros::generate_grpc_client(
+    proto_file: "./proto/Fixture.proto",
+    crate_ns: "grpc_client_tests::fixture_grpc",
+    overrides: [
+        { fn_name: "Set", rename_to: "get_with_ctx" }
+    ]
+)]
+pub struct ReservedClient;
+
+fn main() {}
@@ -0,0 +1,11 @@
+// Hedge which starts right away only doubles the calls
+#[my_grpc_client_macros::generate_grpc_client(
+    proto_file: "./proto/Fixture.proto",
+    crate_ns: "grpc_client_tests::fixture_grpc",
+    overrides: [
+        { fn_name: "Get", hedge: { after: "0ms", max_extra: 1 } }
+    ]
+)]
+pub struct ZeroHedgeClient;
+
+fn main() {}
@@ -0,0 +1,46 @@
+use std::sync::{atomic::AtomicUsize, Arc};
+
+use grpc_client_tests::{get_handler, get_request};
+
+mod client {
+    /// Client with fields of its own
+    #[my_grpc_client_macros::generate_grpc_client(
+        proto_file: "./proto/Fixture.proto",
+        crate_ns: "grpc_client_tests::fixture_grpc",
+        fake_server: true,
+    )]
+    pub struct FieldsClient {
+        pub app_name: String,
+        #[grpc_client(default)]
+        pub calls: std::sync::Mutex<Vec<String>>,
+        #[cfg(not(test))]
+        pub never_compiled: usize,
+        #[cfg(test)]
+        pub test_only: usize,
+    }
+}
+
+use client::{FieldsClient, FieldsClientFakeServer};
+
+#[tokio::test(start_paused = true)]
+async fn test_user_fields_are_kept() {
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let server = FieldsClientFakeServer::new()
+        .on_get(get_handler(calls.clone(), std::time::Duration::ZERO))
+        .start()
+        .await
+        .unwrap();
+
+    // Field marked with #[grpc_client(default)] and the one which is cfg'd out are not parameters
+    let client = FieldsClient::new(Arc::new(server), "my-app".to_string(), 5);
+
+    assert_eq!(client.app_name, "my-app");
+    assert_eq!(client.test_only, 5);
+    assert!(client.calls.lock().unwrap().is_empty());
+
+    client.calls.lock().unwrap().push("get".to_string());
+    client.get(get_request("a")).await.unwrap();
+
+    assert_eq!(client.calls.lock().unwrap().len(), 1);
+}